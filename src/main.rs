@@ -1,12 +1,14 @@
 use eframe::egui;
 use egui::{
-    Color32, FontFamily, FontId, RichText, Stroke, Vec2, Ui, Context, CentralPanel, SidePanel, TopBottomPanel
+    Color32, FontFamily, FontId, RichText, Stroke, Vec2, Ui, Context, CentralPanel, SidePanel, TopBottomPanel, WidgetText
 };
 use chrono::{DateTime, Local};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TriageLevel {
     Critical,
     High,
@@ -15,6 +17,8 @@ pub enum TriageLevel {
 }
 
 impl TriageLevel {
+    const ALL: [TriageLevel; 4] = [TriageLevel::Critical, TriageLevel::High, TriageLevel::Medium, TriageLevel::Low];
+    
     fn color(&self) -> Color32 {
         match self {
             TriageLevel::Critical => Color32::from_rgb(231, 76, 60),
@@ -24,7 +28,7 @@ impl TriageLevel {
         }
     }
     
-    fn text(&self) -> &str {
+    fn text(&self) -> &'static str {
         match self {
             TriageLevel::Critical => "CRITICAL",
             TriageLevel::High => "HIGH",
@@ -32,9 +36,22 @@ impl TriageLevel {
             TriageLevel::Low => "LOW",
         }
     }
+    
+    /// Parses an intake triage field; blank or "auto" means no level was given.
+    fn parse_requested(field: &str) -> Result<Option<TriageLevel>, String> {
+        let field = field.trim();
+        if field.is_empty() || field.eq_ignore_ascii_case("auto") {
+            return Ok(None);
+        }
+        TriageLevel::ALL
+            .into_iter()
+            .find(|level| level.text().eq_ignore_ascii_case(field))
+            .map(Some)
+            .ok_or_else(|| format!("unknown triage level \"{}\"", field))
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VitalSigns {
     blood_pressure: (i32, i32),
     heart_rate: i32,
@@ -42,39 +59,372 @@ pub struct VitalSigns {
     temperature: f32,
 }
 
-impl VitalSigns {
-    fn bp_status(&self) -> TriageLevel {
-        if self.blood_pressure.0 > 180 || self.blood_pressure.1 > 120 {
+// Bounds a vitals reading must fall within to be accepted
+const SYSTOLIC_BOUNDS: std::ops::RangeInclusive<i32> = 40..=300;
+const DIASTOLIC_BOUNDS: std::ops::RangeInclusive<i32> = 20..=200;
+const HEART_RATE_BOUNDS: std::ops::RangeInclusive<i32> = 0..=300;
+const OXYGEN_BOUNDS: std::ops::RangeInclusive<i32> = 50..=100;
+const TEMPERATURE_BOUNDS: std::ops::RangeInclusive<f32> = 25.0..=45.0;
+
+/// Returns a human-readable error for every field outside its bounds.
+fn validate_vitals(vitals: &VitalSigns) -> Vec<String> {
+    let mut errors = Vec::new();
+    
+    if !SYSTOLIC_BOUNDS.contains(&vitals.blood_pressure.0) {
+        errors.push(format!(
+            "Systolic BP must be {}-{} mmHg",
+            SYSTOLIC_BOUNDS.start(),
+            SYSTOLIC_BOUNDS.end()
+        ));
+    }
+    if !DIASTOLIC_BOUNDS.contains(&vitals.blood_pressure.1) {
+        errors.push(format!(
+            "Diastolic BP must be {}-{} mmHg",
+            DIASTOLIC_BOUNDS.start(),
+            DIASTOLIC_BOUNDS.end()
+        ));
+    }
+    if !HEART_RATE_BOUNDS.contains(&vitals.heart_rate) {
+        errors.push(format!(
+            "Heart rate must be {}-{} bpm",
+            HEART_RATE_BOUNDS.start(),
+            HEART_RATE_BOUNDS.end()
+        ));
+    }
+    if !OXYGEN_BOUNDS.contains(&vitals.oxygen_saturation) {
+        errors.push(format!(
+            "O2 saturation must be {}-{}%",
+            OXYGEN_BOUNDS.start(),
+            OXYGEN_BOUNDS.end()
+        ));
+    }
+    if !TEMPERATURE_BOUNDS.contains(&vitals.temperature) {
+        errors.push(format!(
+            "Temperature must be {:.1}-{:.1} °C",
+            TEMPERATURE_BOUNDS.start(),
+            TEMPERATURE_BOUNDS.end()
+        ));
+    }
+    
+    errors
+}
+
+/// A reading that passes per-field bounds but is implausible in combination.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Flags physiologically impossible combinations, typically data-entry transpositions
+/// or bad feed data that `validate_vitals` cannot see field by field.
+fn vitals_sanity_check(vitals: &VitalSigns) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut warn = |message: String| warnings.push(Warning { message });
+    let (systolic, diastolic) = vitals.blood_pressure;
+    
+    if systolic <= diastolic {
+        warn(format!(
+            "Systolic {} is not above diastolic {}; values may be transposed",
+            systolic, diastolic
+        ));
+    }
+    if vitals.heart_rate == 0 && vitals.oxygen_saturation > 0 {
+        warn(format!(
+            "Heart rate 0 with O2 sat {}%; pulse oximetry needs a pulse",
+            vitals.oxygen_saturation
+        ));
+    }
+    if vitals.heart_rate == 0 && systolic > 0 {
+        warn(format!("Heart rate 0 with a measured blood pressure of {}/{}", systolic, diastolic));
+    }
+    
+    warnings
+}
+
+/// Where a vital turns High and then Critical. A reading is flagged below a `*_below` limit
+/// or at/above a `*_from` limit; `None` means that side is never flagged.
+#[derive(Debug, Clone, Copy)]
+pub struct VitalThresholds {
+    name: &'static str,
+    unit: &'static str,
+    /// Smallest difference between readings, used to print inclusive band edges.
+    step: f32,
+    high_below: Option<f32>,
+    high_from: Option<f32>,
+    critical_below: Option<f32>,
+    critical_from: Option<f32>,
+}
+
+const SYSTOLIC_THRESHOLDS: VitalThresholds = VitalThresholds {
+    name: "Systolic BP",
+    unit: "mmHg",
+    step: 1.0,
+    high_below: None,
+    high_from: Some(141.0),
+    critical_below: None,
+    critical_from: Some(181.0),
+};
+const DIASTOLIC_THRESHOLDS: VitalThresholds = VitalThresholds {
+    name: "Diastolic BP",
+    unit: "mmHg",
+    step: 1.0,
+    high_below: None,
+    high_from: Some(91.0),
+    critical_below: None,
+    critical_from: Some(121.0),
+};
+const HEART_RATE_THRESHOLDS: VitalThresholds = VitalThresholds {
+    name: "Heart Rate",
+    unit: "bpm",
+    step: 1.0,
+    high_below: Some(60.0),
+    high_from: Some(101.0),
+    critical_below: Some(50.0),
+    critical_from: Some(121.0),
+};
+const OXYGEN_THRESHOLDS: VitalThresholds = VitalThresholds {
+    name: "O2 Sat",
+    unit: "%",
+    step: 1.0,
+    high_below: Some(95.0),
+    high_from: None,
+    critical_below: Some(90.0),
+    critical_from: None,
+};
+const TEMPERATURE_THRESHOLDS: VitalThresholds = VitalThresholds {
+    name: "Temperature",
+    unit: "°C",
+    step: 0.1,
+    high_below: Some(36.0),
+    high_from: Some(38.0),
+    critical_below: Some(35.0),
+    critical_from: Some(39.5),
+};
+
+/// Every vital's thresholds, in display order.
+const ALL_VITAL_THRESHOLDS: [VitalThresholds; 5] = [
+    SYSTOLIC_THRESHOLDS,
+    DIASTOLIC_THRESHOLDS,
+    HEART_RATE_THRESHOLDS,
+    OXYGEN_THRESHOLDS,
+    TEMPERATURE_THRESHOLDS,
+];
+
+impl VitalThresholds {
+    fn classify(&self, value: f32) -> TriageLevel {
+        let outside = |below: Option<f32>, from: Option<f32>| {
+            below.is_some_and(|b| value < b) || from.is_some_and(|f| value >= f)
+        };
+        if outside(self.critical_below, self.critical_from) {
             TriageLevel::Critical
-        } else if self.blood_pressure.0 > 140 || self.blood_pressure.1 > 90 {
+        } else if outside(self.high_below, self.high_from) {
             TriageLevel::High
         } else {
             TriageLevel::Low
         }
     }
     
-    fn hr_status(&self) -> TriageLevel {
-        if self.heart_rate < 50 || self.heart_rate > 120 {
-            TriageLevel::Critical
-        } else if self.heart_rate < 60 || self.heart_rate > 100 {
-            TriageLevel::High
+    fn format(&self, value: f32) -> String {
+        if self.step < 1.0 {
+            format!("{:.1}", value)
         } else {
-            TriageLevel::Low
+            format!("{:.0}", value)
+        }
+    }
+    
+    /// Inclusive band `lo..=hi` as text, open-ended when a side is `None`.
+    fn band(&self, lo: Option<f32>, hi: Option<f32>) -> String {
+        match (lo, hi) {
+            (Some(lo), Some(hi)) => format!("{}–{}", self.format(lo), self.format(hi)),
+            (Some(lo), None) => format!("≥ {}", self.format(lo)),
+            (None, Some(hi)) => format!("≤ {}", self.format(hi)),
+            (None, None) => "any".to_string(),
         }
     }
     
+    fn normal_text(&self) -> String {
+        self.band(self.high_below, self.high_from.map(|f| f - self.step))
+    }
+    
+    fn high_text(&self) -> String {
+        let low_side = self.high_below.map(|b| self.band(self.critical_below, Some(b - self.step)));
+        let high_side = self.high_from.map(|f| self.band(Some(f), self.critical_from.map(|c| c - self.step)));
+        [low_side, high_side].into_iter().flatten().collect::<Vec<_>>().join(" or ")
+    }
+    
+    fn critical_text(&self) -> String {
+        let low_side = self.critical_below.map(|b| format!("< {}", self.format(b)));
+        let high_side = self.critical_from.map(|f| format!("≥ {}", self.format(f)));
+        [low_side, high_side].into_iter().flatten().collect::<Vec<_>>().join(" or ")
+    }
+}
+
+impl VitalSigns {
+    fn bp_status(&self) -> TriageLevel {
+        let systolic = SYSTOLIC_THRESHOLDS.classify(self.blood_pressure.0 as f32);
+        let diastolic = DIASTOLIC_THRESHOLDS.classify(self.blood_pressure.1 as f32);
+        TriageLevel::ALL
+            .into_iter()
+            .find(|level| *level == systolic || *level == diastolic)
+            .unwrap_or(TriageLevel::Low)
+    }
+    
+    fn hr_status(&self) -> TriageLevel {
+        HEART_RATE_THRESHOLDS.classify(self.heart_rate as f32)
+    }
+    
     fn o2_status(&self) -> TriageLevel {
-        if self.oxygen_saturation < 90 {
-            TriageLevel::Critical
-        } else if self.oxygen_saturation < 95 {
-            TriageLevel::High
+        OXYGEN_THRESHOLDS.classify(self.oxygen_saturation as f32)
+    }
+    
+    fn temp_status(&self) -> TriageLevel {
+        TEMPERATURE_THRESHOLDS.classify(self.temperature)
+    }
+    
+    /// NEWS2 aggregate from the parameters we record (SpO2 scale 1, systolic BP, pulse,
+    /// temperature). Respiration rate, consciousness and supplemental O2 are not captured,
+    /// so this is a partial score suited to trending rather than absolute thresholds.
+    fn news2(&self) -> u32 {
+        let spo2 = match self.oxygen_saturation {
+            ..=91 => 3,
+            92..=93 => 2,
+            94..=95 => 1,
+            _ => 0,
+        };
+        let systolic = match self.blood_pressure.0 {
+            ..=90 => 3,
+            91..=100 => 2,
+            101..=110 => 1,
+            111..=219 => 0,
+            _ => 3,
+        };
+        let pulse = match self.heart_rate {
+            ..=40 => 3,
+            41..=50 => 1,
+            51..=90 => 0,
+            91..=110 => 1,
+            111..=130 => 2,
+            _ => 3,
+        };
+        let temperature = match self.temperature {
+            t if t <= 35.0 => 3,
+            t if t <= 36.0 => 1,
+            t if t <= 38.0 => 0,
+            t if t <= 39.0 => 1,
+            _ => 2,
+        };
+        spo2 + systolic + pulse + temperature
+    }
+    
+    /// The most severe of the individual vital statuses.
+    fn overall_triage(&self) -> TriageLevel {
+        let statuses = [self.bp_status(), self.hr_status(), self.o2_status(), self.temp_status()];
+        TriageLevel::ALL
+            .into_iter()
+            .find(|level| statuses.contains(level))
+            .unwrap_or(TriageLevel::Low)
+    }
+}
+
+/// A timestamped vitals reading kept in a patient's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VitalsSample {
+    vitals: VitalSigns,
+    recorded_at: DateTime<Local>,
+}
+
+/// Most recent history samples the trend is computed over.
+const TREND_SAMPLES: usize = 6;
+/// Fewest samples needed before a trend is reported.
+const TREND_MIN_SAMPLES: usize = 3;
+/// NEWS2 points per sample the fitted slope must exceed to count as a trend.
+const TREND_SLOPE_THRESHOLD: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VitalsTrend {
+    Improving,
+    Stable,
+    Deteriorating,
+    InsufficientData,
+}
+
+impl VitalsTrend {
+    /// Classifies the least-squares slope of NEWS2 over the last `TREND_SAMPLES` samples.
+    fn from_history(history: &[VitalsSample]) -> VitalsTrend {
+        let recent = &history[history.len().saturating_sub(TREND_SAMPLES)..];
+        if recent.len() < TREND_MIN_SAMPLES {
+            return VitalsTrend::InsufficientData;
+        }
+        
+        let n = recent.len() as f32;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = recent.iter().map(|s| s.vitals.news2() as f32).sum::<f32>() / n;
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (i, sample) in recent.iter().enumerate() {
+            let dx = i as f32 - mean_x;
+            covariance += dx * (sample.vitals.news2() as f32 - mean_y);
+            variance += dx * dx;
+        }
+        let slope = covariance / variance;
+        
+        if slope > TREND_SLOPE_THRESHOLD {
+            VitalsTrend::Deteriorating
+        } else if slope < -TREND_SLOPE_THRESHOLD {
+            VitalsTrend::Improving
         } else {
-            TriageLevel::Low
+            VitalsTrend::Stable
+        }
+    }
+    
+    fn text(&self) -> &'static str {
+        match self {
+            VitalsTrend::Improving => "↘ Improving",
+            VitalsTrend::Stable => "→ Stable",
+            VitalsTrend::Deteriorating => "↗ Deteriorating",
+            VitalsTrend::InsufficientData => "Trend: insufficient data",
+        }
+    }
+    
+    fn color(&self) -> Color32 {
+        match self {
+            VitalsTrend::Improving => Color32::from_rgb(46, 204, 113),
+            VitalsTrend::Stable => Color32::from_rgb(52, 152, 219),
+            VitalsTrend::Deteriorating => Color32::from_rgb(231, 76, 60),
+            VitalsTrend::InsufficientData => Color32::from_gray(140),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// How much vitals history is kept per patient.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VitalsRetention {
+    LastSamples(usize),
+    LastMinutes(i64),
+}
+
+impl VitalsRetention {
+    fn apply(&self, history: &mut Vec<VitalsSample>, now: DateTime<Local>) {
+        match *self {
+            VitalsRetention::LastSamples(max) => {
+                if history.len() > max {
+                    history.drain(..history.len() - max);
+                }
+            }
+            VitalsRetention::LastMinutes(minutes) => {
+                let cutoff = now - chrono::Duration::minutes(minutes);
+                history.retain(|sample| sample.recorded_at >= cutoff);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Patient {
     id: String,
     age: u8,
@@ -82,803 +432,7163 @@ pub struct Patient {
     chief_complaint: String,
     triage_level: TriageLevel,
     vitals: VitalSigns,
+    vitals_history: Vec<VitalsSample>,
     location: String,
+    /// Explicit scene zone; when unset the zone is derived from `location`.
+    zone: Option<String>,
     eta_minutes: Option<u32>,
     ambulance_id: Option<String>,
     paramedic: Option<String>,
+    /// Hospital the patient has been routed to, by name.
+    assigned_hospital: Option<String>,
+    #[allow(dead_code)]
     notes: Vec<String>,
     timestamp: DateTime<Local>,
+    /// When telemetry for this patient (ETA, vitals) was last received.
+    last_updated: DateTime<Local>,
+    /// When triage was last assessed or confirmed.
+    last_triage_at: DateTime<Local>,
+    accepted: bool,
+    timeline: Vec<TimelineEvent>,
+    /// Active resuscitation, if a code has been called for this patient.
+    code: Option<CodeSession>,
+    /// Who last acted on this patient, and when.
+    #[serde(default)]
+    last_touched: Option<LastTouch>,
+    /// Where the patient is likely headed, for downstream bed planning.
+    #[serde(default)]
+    expected_disposition: Option<Disposition>,
+    /// Short prehospital observations, oldest first.
+    #[serde(default)]
+    field_notes: Vec<FieldNote>,
+    #[serde(default)]
+    status: PatientStatus,
+    /// When `status` last changed; drives auto-archiving.
+    #[serde(default)]
+    status_changed_at: Option<DateTime<Local>>,
+    /// Where the patient is inside the hospital; `location` stays the prehospital scene.
+    #[serde(default)]
+    in_hospital_location: Option<InHospitalLocation>,
+    /// When the patient was accepted; `None` if not yet accepted or accepted before this was recorded.
+    #[serde(default)]
+    accepted_at: Option<DateTime<Local>>,
+    /// Triage was defaulted at intake and still needs a clinician's confirmation.
+    #[serde(default)]
+    triage_needs_review: bool,
+    /// Meds, labs and imaging placed for this patient, oldest first.
+    #[serde(default)]
+    orders: Vec<Order>,
+    /// Every triage level change, oldest first.
+    #[serde(default)]
+    triage_history: Vec<TriageChange>,
+    /// No vitals have been taken yet; `vitals` holds placeholder values that must not be
+    /// shown, sampled or shared until then.
+    #[serde(default)]
+    awaiting_vitals: bool,
+    /// When the ETA was last set on the board (re-route or reassignment) rather than by
+    /// telemetry; the countdown runs from whichever is newer.
+    #[serde(default)]
+    eta_set_at: Option<DateTime<Local>>,
+    /// The unit in `ambulance_id` has been returned to the available pool.
+    #[serde(default)]
+    ambulance_released: bool,
 }
 
-#[derive(Debug, Clone)]
-pub struct Hospital {
-    name: String,
-    available_beds: u32,
-    total_beds: u32,
-    distance_minutes: u32,
-    specialties: Vec<String>,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatientStatus {
+    #[default]
+    EnRoute,
+    InTriage,
+    InTreatment,
+    Admitted,
+    Discharged,
+    Transferred,
 }
 
-#[derive(Debug, Clone)]
-pub struct Specialist {
-    name: String,
-    specialty: String,
-    available: bool,
-    on_call: bool,
+impl PatientStatus {
+    const ALL: [PatientStatus; 6] = [
+        PatientStatus::EnRoute,
+        PatientStatus::InTriage,
+        PatientStatus::InTreatment,
+        PatientStatus::Admitted,
+        PatientStatus::Discharged,
+        PatientStatus::Transferred,
+    ];
+    
+    fn text(&self) -> &'static str {
+        match self {
+            PatientStatus::EnRoute => "En Route",
+            PatientStatus::InTriage => "In Triage",
+            PatientStatus::InTreatment => "In Treatment",
+            PatientStatus::Admitted => "Admitted",
+            PatientStatus::Discharged => "Discharged",
+            PatientStatus::Transferred => "Transferred",
+        }
+    }
+    
+    /// Statuses where the patient has a place inside the hospital.
+    fn is_in_hospital(&self) -> bool {
+        matches!(self, PatientStatus::InTreatment | PatientStatus::Admitted)
+    }
+    
+    /// Statuses that leave the active board once the archive delay passes.
+    fn is_finished(&self) -> bool {
+        matches!(self, PatientStatus::Discharged | PatientStatus::Transferred)
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct ChatMessage {
-    id: Uuid,
-    sender: String,
-    message: String,
-    timestamp: DateTime<Local>,
-    urgent: bool,
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InHospitalLocation {
+    building: String,
+    floor: String,
+    bay: String,
 }
 
-#[derive(Debug)]
-pub struct EmergencyApp {
-    patients: Vec<Patient>,
-    hospitals: Vec<Hospital>,
-    specialists: Vec<Specialist>,
-    chat_messages: Vec<ChatMessage>,
-    active_tab: usize,
-    chat_input: String,
-    selected_patient: Option<usize>,
-    ambulance_available: u32,
-    ambulance_en_route: u32,
-    ambulance_at_scene: u32,
+impl std::fmt::Display for InHospitalLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} • Floor {} • {}", self.building, self.floor, self.bay)
+    }
 }
 
-impl Default for EmergencyApp {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldNote {
+    at: DateTime<Local>,
+    author: String,
+    text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Pending,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    text: String,
+    ordered_by: String,
+    at: DateTime<Local>,
+    status: OrderStatus,
+}
+
+/// Field notes shown in the rolling feed on an inbound card.
+const FIELD_NOTES_FEED_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disposition {
+    Discharge,
+    Admit,
+    Icu,
+    Or,
+    Transfer,
+}
+
+impl Disposition {
+    const ALL: [Disposition; 5] = [
+        Disposition::Discharge,
+        Disposition::Admit,
+        Disposition::Icu,
+        Disposition::Or,
+        Disposition::Transfer,
+    ];
+    
+    fn color(&self) -> Color32 {
+        match self {
+            Disposition::Discharge => Color32::from_rgb(46, 204, 113),
+            Disposition::Admit => Color32::from_rgb(52, 152, 219),
+            Disposition::Icu => Color32::from_rgb(231, 76, 60),
+            Disposition::Or => Color32::from_rgb(155, 89, 182),
+            Disposition::Transfer => Color32::from_rgb(127, 140, 141),
+        }
+    }
+    
+    fn text(&self) -> &'static str {
+        match self {
+            Disposition::Discharge => "Discharge",
+            Disposition::Admit => "Admit",
+            Disposition::Icu => "ICU",
+            Disposition::Or => "OR",
+            Disposition::Transfer => "Transfer",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastTouch {
+    by: String,
+    at: DateTime<Local>,
+}
+
+/// A running resuscitation (cardiac arrest) with its timestamped interventions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeSession {
+    started_at: DateTime<Local>,
+    interventions: Vec<TimelineEvent>,
+}
+
+impl CodeSession {
+    fn elapsed_secs(&self, now: DateTime<Local>) -> i64 {
+        (now - self.started_at).num_seconds().max(0)
+    }
+}
+
+/// Length of a CPR cycle between rhythm checks.
+const CODE_CYCLE_SECS: i64 = 120;
+
+/// How long the cycle reminder pulses at the start of each cycle.
+const CODE_CYCLE_ALERT_SECS: i64 = 10;
+
+const CODE_INTERVENTIONS: [&str; 4] = ["Epinephrine 1 mg", "Shock delivered", "Amiodarone 300 mg", "Airway secured"];
+
+fn format_mm_ss(secs: i64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    at: DateTime<Local>,
+    text: String,
+}
+
+/// A change of `triage_level`, kept for acuity review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageChange {
+    at: DateTime<Local>,
+    from: TriageLevel,
+    to: TriageLevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HourFormat {
+    H24,
+    H12,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateOrder {
+    DayMonth,
+    MonthDay,
+}
+
+/// Presets that fill in `LocaleSettings` for a language/region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalePreset {
+    EnglishUae,
+    EnglishUs,
+    Arabic,
+}
+
+impl LocalePreset {
+    const ALL: [LocalePreset; 3] = [LocalePreset::EnglishUae, LocalePreset::EnglishUs, LocalePreset::Arabic];
+    
+    fn text(&self) -> &'static str {
+        match self {
+            LocalePreset::EnglishUae => "English (UAE)",
+            LocalePreset::EnglishUs => "English (US)",
+            LocalePreset::Arabic => "العربية",
+        }
+    }
+    
+    fn settings(&self) -> LocaleSettings {
+        let (hour_format, date_order, arabic_indic_digits) = match self {
+            LocalePreset::EnglishUae => (HourFormat::H24, DateOrder::DayMonth, false),
+            LocalePreset::EnglishUs => (HourFormat::H12, DateOrder::MonthDay, false),
+            LocalePreset::Arabic => (HourFormat::H12, DateOrder::DayMonth, true),
+        };
+        LocaleSettings {
+            hour_format,
+            date_order,
+            arabic_indic_digits,
+            ..LocaleSettings::default()
+        }
+    }
+}
+
+/// How times, dates and numbers are shown in the UI. Exported files stay in ISO form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocaleSettings {
+    hour_format: HourFormat,
+    date_order: DateOrder,
+    arabic_indic_digits: bool,
+    /// Shown after the header clock.
+    timezone_label: String,
+}
+
+impl Default for LocaleSettings {
     fn default() -> Self {
         Self {
-            patients: create_demo_patients(),
-            hospitals: create_demo_hospitals(),
-            specialists: create_demo_specialists(),
-            chat_messages: create_demo_messages(),
-            active_tab: 0,
-            chat_input: String::new(),
-            selected_patient: None,
-            ambulance_available: 12,
-            ambulance_en_route: 8,
-            ambulance_at_scene: 3,
+            hour_format: HourFormat::H24,
+            date_order: DateOrder::DayMonth,
+            arabic_indic_digits: false,
+            timezone_label: "GST".to_string(),
         }
     }
 }
 
-impl eframe::App for EmergencyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Configure fonts and style
-        self.configure_fonts(ctx);
-        
-        // Set dark theme
-        ctx.set_visuals(egui::Visuals::dark());
-        
-        // Request repaint every second for real-time updates
-        ctx.request_repaint_after(std::time::Duration::from_secs(1));
-        
-        // Header
-        TopBottomPanel::top("header").show(ctx, |ui| {
-            self.render_header(ui);
-        });
-        
-        // Left sidebar
-        SidePanel::left("sidebar").min_width(280.0).show(ctx, |ui| {
-            self.render_sidebar(ui);
-        });
-        
-        // Right chat panel
-        SidePanel::right("chat").min_width(300.0).show(ctx, |ui| {
-            self.render_chat_panel(ui);
-        });
-        
-        // Main content area
-        CentralPanel::default().show(ctx, |ui| {
-            self.render_main_content(ui);
-        });
+impl LocaleSettings {
+    fn time(&self, at: DateTime<Local>, seconds: bool) -> String {
+        let pattern = match (self.hour_format, seconds) {
+            (HourFormat::H24, true) => "%H:%M:%S",
+            (HourFormat::H24, false) => "%H:%M",
+            (HourFormat::H12, true) => "%I:%M:%S %p",
+            (HourFormat::H12, false) => "%I:%M %p",
+        };
+        self.digits(&at.format(pattern).to_string())
+    }
+    
+    fn date_time(&self, at: DateTime<Local>) -> String {
+        let date = match self.date_order {
+            DateOrder::DayMonth => at.format("%d %b"),
+            DateOrder::MonthDay => at.format("%b %d"),
+        };
+        format!("{} {}", self.digits(&date.to_string()), self.time(at, false))
+    }
+    
+    /// Rewrites ASCII digits as Arabic-Indic digits when enabled.
+    fn digits(&self, text: &str) -> String {
+        if !self.arabic_indic_digits {
+            return text.to_string();
+        }
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) => char::from_u32('\u{0660}' as u32 + d).unwrap_or(c),
+                None => c,
+            })
+            .collect()
     }
 }
 
-impl EmergencyApp {
-    fn configure_fonts(&self, ctx: &Context) {
-        // Using default fonts for now - in production you can add custom fonts
-        let fonts = egui::FontDefinitions::default();
-        ctx.set_fonts(fonts);
+fn format_hh_mm_ss(secs: i64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// A declared mass-casualty incident, timed from declaration to stand-down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    declared_at: DateTime<Local>,
+    declared_by: String,
+    stood_down_at: Option<DateTime<Local>>,
+    /// The incident report, oldest first.
+    log: Vec<TimelineEvent>,
+}
+
+impl Incident {
+    fn declare(by: &str) -> Self {
+        let now = Local::now();
+        Self {
+            declared_at: now,
+            declared_by: by.to_string(),
+            stood_down_at: None,
+            log: vec![TimelineEvent {
+                at: now,
+                text: format!("MCI declared by {}", by),
+            }],
+        }
     }
     
-    fn render_header(&mut self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            ui.add_space(10.0);
-            
-            // Logo and title
-            ui.label(
-                RichText::new("🏥 Dubai Health Authority - Emergency Response")
-                    .font(FontId::new(18.0, FontFamily::Proportional))
+    fn is_active(&self) -> bool {
+        self.stood_down_at.is_none()
+    }
+    
+    /// Elapsed seconds since declaration, frozen at stand-down.
+    fn elapsed_secs(&self, now: DateTime<Local>) -> i64 {
+        (self.stood_down_at.unwrap_or(now) - self.declared_at).num_seconds().max(0)
+    }
+    
+    fn stand_down(&mut self, by: &str) {
+        let now = Local::now();
+        self.stood_down_at = Some(now);
+        self.log.push(TimelineEvent {
+            at: now,
+            text: format!("Stood down by {}; incident duration {}", by, format_hh_mm_ss(self.elapsed_secs(now))),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hospital {
+    name: String,
+    available_beds: u32,
+    total_beds: u32,
+    distance_minutes: u32,
+    /// Freeform, for display only; routing uses the capability flags below.
+    specialties: Vec<String>,
+    /// Manually placed on ambulance divert.
+    on_divert: bool,
+    #[serde(default)]
+    trauma_center: bool,
+    #[serde(default)]
+    stroke_ready: bool,
+    #[serde(default)]
+    cath_lab: bool,
+    #[serde(default)]
+    pediatric: bool,
+    #[serde(default)]
+    burn_unit: bool,
+}
+
+/// A service a patient may need and a hospital may offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Capability {
+    Trauma,
+    Stroke,
+    CathLab,
+    Pediatric,
+    Burns,
+}
+
+impl Capability {
+    const ALL: [Capability; 5] = [
+        Capability::Trauma,
+        Capability::Stroke,
+        Capability::CathLab,
+        Capability::Pediatric,
+        Capability::Burns,
+    ];
+    
+    fn icon(&self) -> &'static str {
+        match self {
+            Capability::Trauma => "🩸",
+            Capability::Stroke => "🧠",
+            Capability::CathLab => "❤",
+            Capability::Pediatric => "🧸",
+            Capability::Burns => "🔥",
+        }
+    }
+    
+    fn text(&self) -> &'static str {
+        match self {
+            Capability::Trauma => "Trauma center",
+            Capability::Stroke => "Stroke ready",
+            Capability::CathLab => "Cath lab",
+            Capability::Pediatric => "Pediatric",
+            Capability::Burns => "Burn unit",
+        }
+    }
+}
+
+impl Hospital {
+    /// Sets capability flags from the specialty list, for hospitals saved before the flags existed.
+    fn infer_capabilities(&mut self) {
+        let offers = |keyword: &str| self.specialties.iter().any(|s| s.to_lowercase().contains(keyword));
+        let (trauma, stroke, cath, pediatric, burns) =
+            (offers("trauma"), offers("neuro"), offers("cardi"), offers("pediatric"), offers("burn"));
+        
+        self.trauma_center = trauma;
+        self.stroke_ready = stroke;
+        self.cath_lab = cath;
+        self.pediatric = pediatric;
+        self.burn_unit = burns;
+    }
+    
+    fn has(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Trauma => self.trauma_center,
+            Capability::Stroke => self.stroke_ready,
+            Capability::CathLab => self.cath_lab,
+            Capability::Pediatric => self.pediatric,
+            Capability::Burns => self.burn_unit,
+        }
+    }
+    
+    /// Patients en route to this hospital.
+    fn inbound_count(&self, patients: &[Patient], hospitals: &[Hospital], rules: &[RoutingRule]) -> usize {
+        patients
+            .iter()
+            .filter(|p| p.eta_minutes.is_some())
+            .filter(|p| p.destination(hospitals, rules).is_some_and(|h| h.name == self.name))
+            .count()
+    }
+    
+    /// Beds left once every inbound patient has arrived; negative when oversubscribed.
+    fn projected_available(&self, patients: &[Patient], hospitals: &[Hospital], rules: &[RoutingRule]) -> i64 {
+        self.available_beds as i64 - self.inbound_count(patients, hospitals, rules) as i64
+    }
+    
+    fn can_accept_now(&self, patients: &[Patient], hospitals: &[Hospital], rules: &[RoutingRule]) -> bool {
+        !self.on_divert && self.projected_available(patients, hospitals, rules) > 0
+    }
+}
+
+/// Narrows the hospitals shown in the Hospital Status tab.
+#[derive(Debug, Clone, Default)]
+pub struct HospitalFilter {
+    can_accept_now: bool,
+    specialty: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Specialist {
+    name: String,
+    specialty: String,
+    available: bool,
+    on_call: bool,
+}
+
+/// Lays out a label once so later frames only clone the galley's `Arc` to draw it.
+fn cached_label(ui: &Ui, text: RichText) -> WidgetText {
+    WidgetText::Galley(WidgetText::from(text).into_galley(ui, Some(false), f32::INFINITY, egui::TextStyle::Body).galley)
+}
+
+/// Laid-out labels and colors for one sidebar hospital row.
+#[derive(Clone)]
+pub struct SidebarHospitalRow {
+    /// Key for `hospital_rects`.
+    name: String,
+    name_text: WidgetText,
+    bed_color: Color32,
+    bed_text: WidgetText,
+    distance_text: WidgetText,
+}
+
+impl SidebarHospitalRow {
+    fn new(ui: &Ui, hospital: &Hospital) -> Self {
+        let bed_color = if hospital.on_divert {
+            Color32::from_rgb(231, 76, 60)
+        } else if hospital.available_beds > 2 {
+            Color32::from_rgb(46, 204, 113)
+        } else if hospital.available_beds > 0 {
+            Color32::from_rgb(243, 156, 18)
+        } else {
+            Color32::from_rgb(231, 76, 60)
+        };
+        
+        let bed_text = if hospital.on_divert {
+            "On Divert".to_string()
+        } else if hospital.available_beds > 0 {
+            format!("{} Available", hospital.available_beds)
+        } else {
+            "Full Capacity".to_string()
+        };
+        
+        let small = FontId::new(11.0, FontFamily::Proportional);
+        Self {
+            name: hospital.name.clone(),
+            name_text: cached_label(
+                ui,
+                RichText::new(hospital.name.as_str())
+                    .font(FontId::new(13.0, FontFamily::Proportional))
+                    .color(Color32::WHITE)
+                    .strong()
+            ),
+            bed_color,
+            bed_text: cached_label(ui, RichText::new(bed_text).font(small.clone()).color(Color32::LIGHT_GRAY)),
+            distance_text: cached_label(
+                ui,
+                RichText::new(format!("{} min", hospital.distance_minutes)).font(small).color(Color32::LIGHT_GRAY)
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SidebarSpecialistRow {
+    label: WidgetText,
+    status_color: Color32,
+}
+
+impl SidebarSpecialistRow {
+    fn new(ui: &Ui, specialist: &Specialist) -> Self {
+        let status_color = if specialist.available {
+            Color32::from_rgb(46, 204, 113)
+        } else if specialist.on_call {
+            Color32::from_rgb(243, 156, 18)
+        } else {
+            Color32::from_rgb(231, 76, 60)
+        };
+        
+        Self {
+            label: cached_label(
+                ui,
+                RichText::new(format!("{} - {}", specialist.name, specialist.specialty))
+                    .font(FontId::new(12.0, FontFamily::Proportional))
+                    .color(Color32::WHITE)
+            ),
+            status_color,
+        }
+    }
+}
+
+/// Sidebar rows derived from hospitals and specialists. Rebuilt only when
+/// `EmergencyApp::sidebar_dirty` is set, so the once-a-second repaint doesn't re-format
+/// or re-layout every label for unchanged data.
+#[derive(Default)]
+pub struct SidebarCache {
+    hospitals: Vec<SidebarHospitalRow>,
+    specialists: Vec<SidebarSpecialistRow>,
+    /// Index of the focused patient's destination, keyed by the patient it was routed for.
+    focus_destination: Option<(Option<String>, Option<usize>)>,
+    /// Font atlas the galleys were laid out against. egui replaces it when the scale changes
+    /// or it fills up, which leaves cached galleys pointing at stale glyphs.
+    font_atlas: Option<Arc<egui::mutex::Mutex<egui::epaint::TextureAtlas>>>,
+    rebuilds: u64,
+}
+
+// Galleys and the font atlas have no useful `Debug`; report what the cache holds instead
+impl std::fmt::Debug for SidebarCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SidebarCache")
+            .field("hospitals", &self.hospitals.len())
+            .field("specialists", &self.specialists.len())
+            .field("focus_destination", &self.focus_destination)
+            .field("rebuilds", &self.rebuilds)
+            .finish()
+    }
+}
+
+impl SidebarCache {
+    fn rebuild(&mut self, ui: &Ui, hospitals: &[Hospital], specialists: &[Specialist]) {
+        self.hospitals.clear();
+        self.hospitals.extend(hospitals.iter().map(|h| SidebarHospitalRow::new(ui, h)));
+        self.specialists.clear();
+        self.specialists.extend(specialists.iter().map(|s| SidebarSpecialistRow::new(ui, s)));
+        self.focus_destination = None;
+        self.font_atlas = Some(ui.fonts(|f| f.texture_atlas()));
+        self.rebuilds += 1;
+    }
+    
+    fn fonts_changed(&self, ui: &Ui) -> bool {
+        !self
+            .font_atlas
+            .as_ref()
+            .is_some_and(|atlas| ui.fonts(|f| Arc::ptr_eq(atlas, &f.texture_atlas())))
+    }
+}
+
+/// Frames kept by `FrameTiming`.
+const FRAME_TIMING_SAMPLES: usize = 120;
+
+/// Fixed-size ring of render durations, so measuring doesn't itself allocate per frame.
+#[derive(Debug)]
+pub struct FrameTiming {
+    samples_ms: [f32; FRAME_TIMING_SAMPLES],
+    next: usize,
+    filled: usize,
+}
+
+impl Default for FrameTiming {
+    fn default() -> Self {
+        Self {
+            samples_ms: [0.0; FRAME_TIMING_SAMPLES],
+            next: 0,
+            filled: 0,
+        }
+    }
+}
+
+impl FrameTiming {
+    fn record(&mut self, elapsed: std::time::Duration) {
+        self.samples_ms[self.next] = elapsed.as_secs_f32() * 1000.0;
+        self.next = (self.next + 1) % FRAME_TIMING_SAMPLES;
+        self.filled = (self.filled + 1).min(FRAME_TIMING_SAMPLES);
+    }
+    
+    fn len(&self) -> usize {
+        self.filled
+    }
+    
+    fn average_ms(&self) -> f32 {
+        if self.filled == 0 {
+            return 0.0;
+        }
+        self.samples_ms[..self.filled].iter().sum::<f32>() / self.filled as f32
+    }
+    
+    fn max_ms(&self) -> f32 {
+        self.samples_ms[..self.filled].iter().copied().fold(0.0, f32::max)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    #[allow(dead_code)]
+    id: Uuid,
+    sender: String,
+    message: String,
+    timestamp: DateTime<Local>,
+    urgent: bool,
+    #[serde(default)]
+    attachment: Option<VitalsAttachment>,
+    /// Roles @-mentioned by an automated escalation.
+    #[serde(default)]
+    mentions: Vec<StaffRole>,
+    /// Posted by the app rather than a team member; excluded from presence.
+    #[serde(default)]
+    automated: bool,
+}
+
+/// A patient's vitals as they were when a chat message was sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VitalsAttachment {
+    patient_id: String,
+    vitals: VitalSigns,
+    captured_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Online,
+    Away,
+}
+
+impl Presence {
+    fn color(&self) -> Color32 {
+        match self {
+            Presence::Online => Color32::from_rgb(46, 204, 113),
+            Presence::Away => Color32::from_rgb(243, 156, 18),
+        }
+    }
+    
+    fn text(&self) -> &'static str {
+        match self {
+            Presence::Online => "online",
+            Presence::Away => "away",
+        }
+    }
+}
+
+/// Who is reachable, derived from chat activity until a networked roster exists. Each
+/// distinct sender is online if they posted within `online_minutes`, away within
+/// `away_minutes`, and omitted after that. The current user is always online.
+fn team_presence(
+    messages: &[ChatMessage],
+    current_user: &str,
+    now: DateTime<Local>,
+    online_minutes: i64,
+    away_minutes: i64,
+) -> Vec<(String, Presence)> {
+    let mut last_seen: BTreeMap<&str, DateTime<Local>> = BTreeMap::new();
+    for message in messages.iter().filter(|m| !m.automated) {
+        let seen = last_seen.entry(message.sender.as_str()).or_insert(message.timestamp);
+        *seen = (*seen).max(message.timestamp);
+    }
+    last_seen.insert(current_user, now);
+    
+    let mut roster: Vec<(String, Presence)> = last_seen
+        .into_iter()
+        .filter_map(|(sender, seen)| {
+            let idle = (now - seen).num_minutes();
+            let presence = if idle < online_minutes {
+                Presence::Online
+            } else if idle < away_minutes {
+                Presence::Away
+            } else {
+                return None;
+            };
+            Some((sender.to_string(), presence))
+        })
+        .collect();
+    roster.sort_by_key(|(_, presence)| *presence == Presence::Away);
+    roster
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StaffRole {
+    ErDirector,
+    ChargeNurse,
+    Physician,
+    Nurse,
+    Paramedic,
+    BedManager,
+}
+
+impl StaffRole {
+    const ALL: [StaffRole; 6] = [
+        StaffRole::ErDirector,
+        StaffRole::ChargeNurse,
+        StaffRole::Physician,
+        StaffRole::Nurse,
+        StaffRole::Paramedic,
+        StaffRole::BedManager,
+    ];
+    
+    fn text(&self) -> &str {
+        match self {
+            StaffRole::ErDirector => "ER Director",
+            StaffRole::ChargeNurse => "Charge Nurse",
+            StaffRole::Physician => "Physician",
+            StaffRole::Nurse => "Nurse",
+            StaffRole::Paramedic => "Paramedic",
+            StaffRole::BedManager => "Bed Manager",
+        }
+    }
+    
+    /// Whether this role may issue and dismiss facility-wide broadcasts.
+    fn can_broadcast(&self) -> bool {
+        matches!(self, StaffRole::ErDirector | StaffRole::ChargeNurse)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardAction {
+    Accept,
+    Details,
+    CallSpecialist,
+    AddNotes,
+    Reassess,
+    EditVitals,
+    DispatchAmbulance,
+    AssignHospital,
+    StartCode,
+}
+
+impl CardAction {
+    const ALL: [CardAction; 9] = [
+        CardAction::Accept,
+        CardAction::Details,
+        CardAction::CallSpecialist,
+        CardAction::AddNotes,
+        CardAction::Reassess,
+        CardAction::EditVitals,
+        CardAction::DispatchAmbulance,
+        CardAction::AssignHospital,
+        CardAction::StartCode,
+    ];
+    
+    fn text(&self) -> &'static str {
+        match self {
+            CardAction::Accept => "Accept",
+            CardAction::Details => "Details",
+            CardAction::CallSpecialist => "Call Specialist",
+            CardAction::AddNotes => "Add Notes",
+            CardAction::Reassess => "Re-assess",
+            CardAction::EditVitals => "Edit Vitals",
+            CardAction::DispatchAmbulance => "Dispatch Ambulance",
+            CardAction::AssignHospital => "Assign Hospital",
+            CardAction::StartCode => "Start Code",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActionPlacement {
+    Primary,
+    Overflow,
+    Hidden,
+}
+
+impl ActionPlacement {
+    const ALL: [ActionPlacement; 3] = [ActionPlacement::Primary, ActionPlacement::Overflow, ActionPlacement::Hidden];
+    
+    fn text(&self) -> &'static str {
+        match self {
+            ActionPlacement::Primary => "Button",
+            ActionPlacement::Overflow => "⋯ menu",
+            ActionPlacement::Hidden => "Hidden",
+        }
+    }
+}
+
+/// Where one card action appears. Slots are kept in display order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardActionSlot {
+    action: CardAction,
+    placement: ActionPlacement,
+}
+
+/// Card action layout suited to a role. Actions a preset doesn't list go to the overflow menu.
+fn card_action_preset(role: StaffRole) -> Vec<CardActionSlot> {
+    use ActionPlacement::{Hidden, Primary};
+    use CardAction::*;
+    
+    let listed: &[(CardAction, ActionPlacement)] = match role {
+        StaffRole::BedManager => &[
+            (AssignHospital, Primary),
+            (Accept, Primary),
+            (Details, Primary),
+            (AddNotes, Hidden),
+            (CallSpecialist, Hidden),
+            (StartCode, Hidden),
+        ],
+        StaffRole::Paramedic => &[
+            (EditVitals, Primary),
+            (AddNotes, Primary),
+            (Details, Primary),
+            (StartCode, Primary),
+        ],
+        StaffRole::ErDirector | StaffRole::ChargeNurse | StaffRole::Physician | StaffRole::Nurse => &[
+            (Accept, Primary),
+            (Details, Primary),
+            (CallSpecialist, Primary),
+            (AddNotes, Primary),
+            (Reassess, Primary),
+            (EditVitals, Primary),
+            (DispatchAmbulance, Primary),
+            (StartCode, Primary),
+        ],
+    };
+    
+    let mut slots: Vec<CardActionSlot> = listed
+        .iter()
+        .map(|&(action, placement)| CardActionSlot { action, placement })
+        .collect();
+    for action in CardAction::ALL {
+        if !slots.iter().any(|slot| slot.action == action) {
+            slots.push(CardActionSlot {
+                action,
+                placement: ActionPlacement::Overflow,
+            });
+        }
+    }
+    slots
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaffMember {
+    name: String,
+    role: StaffRole,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BroadcastSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl BroadcastSeverity {
+    const ALL: [BroadcastSeverity; 3] = [BroadcastSeverity::Info, BroadcastSeverity::Warning, BroadcastSeverity::Critical];
+    
+    fn color(&self) -> Color32 {
+        match self {
+            BroadcastSeverity::Info => Color32::from_rgb(41, 128, 185),
+            BroadcastSeverity::Warning => Color32::from_rgb(243, 156, 18),
+            BroadcastSeverity::Critical => Color32::from_rgb(192, 57, 43),
+        }
+    }
+    
+    fn text(&self) -> &str {
+        match self {
+            BroadcastSeverity::Info => "NOTICE",
+            BroadcastSeverity::Warning => "WARNING",
+            BroadcastSeverity::Critical => "EMERGENCY",
+        }
+    }
+}
+
+/// A facility-wide alert shown above every panel until dismissed by an authorized role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Broadcast {
+    text: String,
+    severity: BroadcastSeverity,
+    issued_by: String,
+    issued_at: DateTime<Local>,
+}
+
+/// Maps a location keyword (e.g. a road name) to a scene zone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneRule {
+    keyword: String,
+    zone: String,
+}
+
+const UNASSIGNED_ZONE: &str = "Unassigned";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RouteTo {
+    Hospital(String),
+    Capability(Capability),
+}
+
+impl RouteTo {
+    fn text(&self) -> String {
+        match self {
+            RouteTo::Hospital(name) => name.clone(),
+            RouteTo::Capability(capability) => format!("nearest {}", capability.text().to_lowercase()),
+        }
+    }
+}
+
+/// Board events that post an automated message to the team chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscalationEvent {
+    NewCritical,
+    NeedsAttention,
+    CodeCalled,
+}
+
+impl EscalationEvent {
+    const ALL: [EscalationEvent; 3] = [
+        EscalationEvent::NewCritical,
+        EscalationEvent::NeedsAttention,
+        EscalationEvent::CodeCalled,
+    ];
+    
+    fn text(&self) -> &'static str {
+        match self {
+            EscalationEvent::NewCritical => "New Critical patient",
+            EscalationEvent::NeedsAttention => "Patient needs attention",
+            EscalationEvent::CodeCalled => "Code called",
+        }
+    }
+}
+
+/// @-mentions `mention` whenever `event` is posted. Events without a route go to the
+/// main channel unmentioned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRoute {
+    event: EscalationEvent,
+    mention: StaffRole,
+}
+
+/// Roles to mention for an event, in configuration order and without repeats.
+fn escalation_mentions(routes: &[EscalationRoute], event: EscalationEvent) -> Vec<StaffRole> {
+    let mut mentions = Vec::new();
+    for route in routes.iter().filter(|r| r.event == event) {
+        if !mentions.contains(&route.mention) {
+            mentions.push(route.mention);
+        }
+    }
+    mentions
+}
+
+/// Seconds a patient must go without needing attention before a new flag is posted again.
+const ATTENTION_REPOST_AFTER_SECS: i64 = 300;
+
+/// Records when each patient was last seen needing attention and returns those newly
+/// flagged, so a flag that flickers (e.g. with the NEWS2 trend) is only posted once.
+fn debounce_attention(
+    last_flagged: &mut HashMap<String, DateTime<Local>>,
+    flagged: &[String],
+    now: DateTime<Local>,
+) -> Vec<String> {
+    last_flagged.retain(|id, at| flagged.contains(id) || (now - *at).num_seconds() < ATTENTION_REPOST_AFTER_SECS);
+    flagged
+        .iter()
+        .filter(|id| last_flagged.insert(id.to_string(), now).is_none())
+        .cloned()
+        .collect()
+}
+
+/// A local routing protocol, e.g. "stroke" → Rashid Hospital. Matched against the chief
+/// complaint and consulted before the nearest-with-beds heuristic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    keyword: String,
+    route_to: RouteTo,
+}
+
+/// Whether `text` contains `term` as whole words, ignoring case and a trailing plural "s".
+fn mentions_term(text: &str, term: &str) -> bool {
+    let split = |s: &str| -> Vec<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let words = split(text);
+    let term = split(term);
+    if term.is_empty() {
+        return false;
+    }
+    
+    words.windows(term.len()).any(|window| {
+        window
+            .iter()
+            .zip(&term)
+            .all(|(word, expected)| word == expected || word.strip_suffix('s') == Some(expected.as_str()))
+    })
+}
+
+impl RoutingRule {
+    fn matches(&self, patient: &Patient) -> bool {
+        mentions_term(&patient.chief_complaint, &self.keyword)
+    }
+}
+
+impl Patient {
+    /// Resolves the scene zone, preferring the explicit `zone` field over keyword rules.
+    fn zone(&self, rules: &[ZoneRule]) -> String {
+        if let Some(zone) = &self.zone {
+            return zone.clone();
+        }
+        
+        let location = self.location.to_lowercase();
+        rules
+            .iter()
+            .find(|rule| !rule.keyword.is_empty() && location.contains(&rule.keyword.to_lowercase()))
+            .map(|rule| rule.zone.clone())
+            .unwrap_or_else(|| UNASSIGNED_ZONE.to_string())
+    }
+    
+    /// Records the current vitals into history, dropping samples outside the retention window.
+    fn record_vitals(&mut self, retention: VitalsRetention, now: DateTime<Local>) {
+        self.vitals_history.push(VitalsSample {
+            vitals: self.vitals.clone(),
+            recorded_at: now,
+        });
+        retention.apply(&mut self.vitals_history, now);
+    }
+    
+    /// Counts down from the last reported ETA. Once telemetry is older than `stale_after_secs`
+    /// the countdown freezes at that point instead of running on toward a false arrival.
+    fn eta_countdown(&self, now: DateTime<Local>, stale_after_secs: i64) -> Option<EtaCountdown> {
+        let eta = self.eta_minutes?;
+        let age = (now - self.last_updated).num_seconds().max(0);
+        let stale = age > stale_after_secs;
+        let eta_from = self.eta_set_at.map_or(self.last_updated, |at| at.max(self.last_updated));
+        let elapsed = (now - eta_from).num_seconds().clamp(0, stale_after_secs);
+        
+        Some(EtaCountdown {
+            remaining_secs: (eta as i64 * 60 - elapsed).max(0),
+            stale,
+        })
+    }
+    
+    fn reassess_due(&self, now: DateTime<Local>, settings: &Settings) -> bool {
+        settings.reassess_enabled
+            && (now - self.last_triage_at).num_minutes() >= settings.reassess_window(self.triage_level)
+    }
+    
+    /// Records a triage re-assessment, which resets the re-assessment timer even if the level is unchanged.
+    fn reassess(&mut self, level: TriageLevel) {
+        if level == self.triage_level {
+            self.log_event(format!("Triage re-assessed: {} confirmed", level.text()));
+        } else {
+            self.log_event(format!("Triage re-assessed: {} → {}", self.triage_level.text(), level.text()));
+            self.triage_history.push(TriageChange {
+                at: Local::now(),
+                from: self.triage_level,
+                to: level,
+            });
+            self.triage_level = level;
+        }
+        self.last_triage_at = Local::now();
+        self.triage_needs_review = false;
+    }
+    
+    /// Triage level as it stood at `at`, replaying `triage_history`.
+    fn triage_at(&self, at: DateTime<Local>) -> TriageLevel {
+        match self.triage_history.iter().rev().find(|change| change.at <= at) {
+            Some(change) => change.to,
+            None => self.triage_history.first().map_or(self.triage_level, |change| change.from),
+        }
+    }
+    
+    /// Whether the patient was on the board at `at`: arrived, and not yet discharged or transferred.
+    fn on_board_at(&self, at: DateTime<Local>) -> bool {
+        let left = self.status.is_finished() && self.status_changed_at.is_some_and(|changed| changed <= at);
+        self.timestamp <= at && !left
+    }
+    
+    /// Icon and text for where the patient is now: the in-hospital location once they are
+    /// in treatment or admitted, otherwise the prehospital scene.
+    fn current_location(&self) -> (&'static str, String) {
+        match &self.in_hospital_location {
+            Some(location) if self.status.is_in_hospital() => ("🏥", location.to_string()),
+            _ => ("📍", self.location.clone()),
+        }
+    }
+    
+    /// Status banner text for patients without an ETA, e.g. "In Treatment - Main • Floor 2 • Bay 4".
+    fn status_line(&self) -> String {
+        let (_, location) = self.current_location();
+        format!("{} - {}", self.status.text(), location)
+    }
+    
+    /// Current vitals, or `None` while they have not been taken.
+    fn recorded_vitals(&self) -> Option<&VitalSigns> {
+        (!self.awaiting_vitals).then_some(&self.vitals)
+    }
+    
+    fn vitals_trend(&self) -> VitalsTrend {
+        VitalsTrend::from_history(&self.vitals_history)
+    }
+    
+    /// Whether the patient should be looked at now: re-assessment is due, triage was
+    /// defaulted and awaits review, or vitals are deteriorating.
+    fn needs_attention(&self, now: DateTime<Local>, settings: &Settings) -> bool {
+        self.reassess_due(now, settings)
+            || self.triage_needs_review
+            || self.vitals_trend() == VitalsTrend::Deteriorating
+    }
+    
+    /// Higher is more urgent. Triage dominates; deranged vitals then time waiting break ties.
+    fn priority_score(&self, now: DateTime<Local>) -> i64 {
+        let rank = |level: TriageLevel| {
+            let index = TriageLevel::ALL.iter().position(|l| *l == level).unwrap_or(TriageLevel::ALL.len());
+            (TriageLevel::ALL.len() - index) as i64
+        };
+        let waiting = (now - self.timestamp).num_minutes().clamp(0, 99);
+        let vitals_rank = self.recorded_vitals().map_or(rank(self.triage_level), |v| rank(v.overall_triage()));
+        rank(self.triage_level) * 1000 + vitals_rank * 100 + waiting
+    }
+    
+    /// Capabilities the receiving hospital should have, from the complaint and age.
+    fn needed_capabilities(&self) -> Vec<Capability> {
+        let mentions = |terms: &[&str]| terms.iter().any(|term| mentions_term(&self.chief_complaint, term));
+        
+        let mut needs = Vec::new();
+        if mentions(&["accident", "trauma", "fall", "fracture", "gunshot", "stab", "collision"]) {
+            needs.push(Capability::Trauma);
+        }
+        if mentions(&["stroke", "facial droop", "slurred speech", "hemiparesis"]) {
+            needs.push(Capability::Stroke);
+        }
+        if mentions(&["chest pain", "cardiac", "heart attack", "stemi"]) {
+            needs.push(Capability::CathLab);
+        }
+        if mentions(&["burn", "scald", "smoke inhalation"]) {
+            needs.push(Capability::Burns);
+        }
+        if self.age < 16 {
+            needs.push(Capability::Pediatric);
+        }
+        needs
+    }
+    
+    /// The assigned hospital, falling back to the current recommendation.
+    fn destination<'a>(&self, hospitals: &'a [Hospital], rules: &[RoutingRule]) -> Option<&'a Hospital> {
+        match &self.assigned_hospital {
+            Some(name) => hospitals.iter().find(|h| &h.name == name),
+            None => recommend_hospital(self, hospitals, rules),
+        }
+    }
+    
+    /// Merges field notes from any source (local capture or a network update), skipping
+    /// ones already present and keeping the feed in chronological order.
+    fn merge_field_notes(&mut self, incoming: impl IntoIterator<Item = FieldNote>) {
+        for note in incoming {
+            if !self.field_notes.contains(&note) {
+                self.field_notes.push(note);
+            }
+        }
+        self.field_notes.sort_by_key(|note| note.at);
+    }
+    
+    fn set_status(&mut self, status: PatientStatus) {
+        if status != self.status {
+            self.log_event(format!("Status: {} → {}", self.status.text(), status.text()));
+            self.status = status;
+            self.status_changed_at = Some(Local::now());
+        }
+    }
+    
+    fn start_code(&mut self) {
+        if self.code.is_none() {
+            self.code = Some(CodeSession {
+                started_at: Local::now(),
+                interventions: vec![],
+            });
+            self.log_event("Code started");
+        }
+    }
+    
+    fn log_intervention(&mut self, intervention: &str) {
+        let Some(code) = &mut self.code else {
+            return;
+        };
+        
+        let now = Local::now();
+        let elapsed = format_mm_ss(code.elapsed_secs(now));
+        code.interventions.push(TimelineEvent {
+            at: now,
+            text: intervention.to_string(),
+        });
+        self.log_event(format!("Code +{}: {}", elapsed, intervention));
+    }
+    
+    fn end_code(&mut self) {
+        if let Some(code) = self.code.take() {
+            self.log_event(format!(
+                "Code ended after {} ({} interventions)",
+                format_mm_ss(code.elapsed_secs(Local::now())),
+                code.interventions.len()
+            ));
+        }
+    }
+    
+    fn log_event(&mut self, text: impl Into<String>) {
+        self.timeline.push(TimelineEvent {
+            at: Local::now(),
+            text: text.into(),
+        });
+    }
+    
+    fn add_order(&mut self, text: &str, ordered_by: &str) {
+        self.orders.push(Order {
+            text: text.to_string(),
+            ordered_by: ordered_by.to_string(),
+            at: Local::now(),
+            status: OrderStatus::Pending,
+        });
+        self.log_event(format!("Order placed: {}", text));
+    }
+    
+    fn complete_order(&mut self, index: usize, by: &str) {
+        let Some(order) = self.orders.get_mut(index).filter(|o| o.status == OrderStatus::Pending) else {
+            return;
+        };
+        order.status = OrderStatus::Completed;
+        let text = order.text.clone();
+        self.log_event(format!("Order completed by {}: {}", by, text));
+    }
+}
+
+/// ETA countdown derived from the last reported ETA and telemetry age.
+#[derive(Debug, Clone, Copy)]
+pub struct EtaCountdown {
+    remaining_secs: i64,
+    stale: bool,
+}
+
+/// Time window covered by the Analytics tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsRange {
+    LastHour,
+    Last4Hours,
+    Last12Hours,
+    Last24Hours,
+}
+
+impl AnalyticsRange {
+    const ALL: [AnalyticsRange; 4] = [
+        AnalyticsRange::LastHour,
+        AnalyticsRange::Last4Hours,
+        AnalyticsRange::Last12Hours,
+        AnalyticsRange::Last24Hours,
+    ];
+    
+    fn text(&self) -> &str {
+        match self {
+            AnalyticsRange::LastHour => "Last hour",
+            AnalyticsRange::Last4Hours => "Last 4 hours",
+            AnalyticsRange::Last12Hours => "Last 12 hours",
+            AnalyticsRange::Last24Hours => "Last 24 hours",
+        }
+    }
+    
+    fn duration(&self) -> chrono::Duration {
+        match self {
+            AnalyticsRange::LastHour => chrono::Duration::hours(1),
+            AnalyticsRange::Last4Hours => chrono::Duration::hours(4),
+            AnalyticsRange::Last12Hours => chrono::Duration::hours(12),
+            AnalyticsRange::Last24Hours => chrono::Duration::hours(24),
+        }
+    }
+}
+
+/// Minutes covered by each bucket of the arrivals sparkline.
+const ARRIVAL_BUCKET_MINUTES: i64 = 5;
+
+/// How many of the most urgent patients "jump to most urgent" cycles through.
+const URGENT_CYCLE_LEN: usize = 5;
+
+/// Dimension Active Emergencies groups patients by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupBy {
+    None,
+    Status,
+    Triage,
+    Hospital,
+    Ambulance,
+}
+
+impl GroupBy {
+    const ALL: [GroupBy; 5] = [GroupBy::None, GroupBy::Status, GroupBy::Triage, GroupBy::Hospital, GroupBy::Ambulance];
+    
+    fn text(&self) -> &'static str {
+        match self {
+            GroupBy::None => "No grouping",
+            GroupBy::Status => "Status",
+            GroupBy::Triage => "Triage",
+            GroupBy::Hospital => "Hospital",
+            GroupBy::Ambulance => "Ambulance",
+        }
+    }
+    
+    /// Splits patients into labelled groups, keeping their order within each group. Groups
+    /// follow the enum order for status and triage, and name order otherwise, with the
+    /// "none" group last.
+    fn group(
+        &self,
+        patients: Vec<Patient>,
+        hospitals: &[Hospital],
+        rules: &[RoutingRule],
+    ) -> Vec<(String, Vec<Patient>)> {
+        let mut groups: BTreeMap<(usize, String), Vec<Patient>> = BTreeMap::new();
+        for patient in patients {
+            let key = match self {
+                GroupBy::None => (0, String::new()),
+                GroupBy::Status => (
+                    PatientStatus::ALL.iter().position(|s| *s == patient.status).unwrap_or(0),
+                    patient.status.text().to_string(),
+                ),
+                GroupBy::Triage => (
+                    TriageLevel::ALL.iter().position(|l| *l == patient.triage_level).unwrap_or(0),
+                    patient.triage_level.text().to_string(),
+                ),
+                GroupBy::Hospital => match patient.destination(hospitals, rules) {
+                    Some(hospital) => (0, hospital.name.clone()),
+                    None => (1, "No destination".to_string()),
+                },
+                GroupBy::Ambulance => match &patient.ambulance_id {
+                    Some(unit) => (0, unit.clone()),
+                    None => (1, "No unit".to_string()),
+                },
+            };
+            groups.entry(key).or_default().push(patient);
+        }
+        groups.into_iter().map(|((_, label), patients)| (label, patients)).collect()
+    }
+}
+
+/// How Active Emergencies lays out patients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardView {
+    Cards,
+    Table,
+}
+
+/// Narrows the patients shown in Active Emergencies.
+#[derive(Debug, Clone, Default)]
+pub struct PatientFilter {
+    reassess_due_only: bool,
+    needs_attention_only: bool,
+    triage: Option<TriageLevel>,
+}
+
+impl PatientFilter {
+    fn matches(&self, patient: &Patient, now: DateTime<Local>, settings: &Settings) -> bool {
+        (!self.reassess_due_only || patient.reassess_due(now, settings))
+            && (!self.needs_attention_only || patient.needs_attention(now, settings))
+            && self.triage.is_none_or(|level| patient.triage_level == level)
+    }
+}
+
+/// Draft vitals being corrected for a patient.
+#[derive(Debug, Clone)]
+pub struct VitalsEditor {
+    patient_id: String,
+    draft: VitalSigns,
+}
+
+/// Checklist state for an accept that is waiting on handover confirmation.
+#[derive(Debug, Clone)]
+pub struct PendingHandover {
+    patient_id: String,
+    checked: Vec<bool>,
+}
+
+/// An accept or hospital assignment waiting on confirmation because the hospital is full or on divert.
+#[derive(Debug, Clone)]
+pub struct PendingOverride {
+    patient_id: String,
+    hospital: String,
+    /// True when confirming assigns the hospital rather than continuing an accept.
+    assign: bool,
+}
+
+/// A patient entered by hand or imported, before triage is resolved.
+#[derive(Debug, Clone)]
+pub struct PatientIntake {
+    age: u8,
+    gender: String,
+    chief_complaint: String,
+    location: String,
+    /// `None` when triage was left blank or set to "auto".
+    triage: Option<TriageLevel>,
+    /// `None` when vitals were not taken or not all fields were given.
+    vitals: Option<VitalSigns>,
+}
+
+impl PatientIntake {
+    /// Parses one CSV line: `age,gender,complaint,location,triage,systolic,diastolic,hr,o2,temp`.
+    /// Vitals columns may be blank or omitted.
+    fn from_csv_line(line: &str) -> Result<PatientIntake, String> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 5 {
+            return Err(format!("expected at least 5 columns, found {}", fields.len()));
+        }
+        
+        let age = fields[0].parse::<u8>().map_err(|_| format!("invalid age \"{}\"", fields[0]))?;
+        let triage = TriageLevel::parse_requested(fields[4])?;
+        
+        let vital = |i: usize| fields.get(i).filter(|f| !f.is_empty());
+        let vitals = match (vital(5), vital(6), vital(7), vital(8), vital(9)) {
+            (Some(sys), Some(dia), Some(hr), Some(o2), Some(temp)) => {
+                let parse_int = |f: &str| f.parse::<i32>().map_err(|_| format!("invalid vitals value \"{}\"", f));
+                let vitals = VitalSigns {
+                    blood_pressure: (parse_int(sys)?, parse_int(dia)?),
+                    heart_rate: parse_int(hr)?,
+                    oxygen_saturation: parse_int(o2)?,
+                    temperature: temp.parse::<f32>().map_err(|_| format!("invalid temperature \"{}\"", temp))?,
+                };
+                if let Some(error) = validate_vitals(&vitals).into_iter().next() {
+                    return Err(error);
+                }
+                if let Some(warning) = vitals_sanity_check(&vitals).into_iter().next() {
+                    return Err(warning.to_string());
+                }
+                Some(vitals)
+            }
+            _ => None,
+        };
+        
+        Ok(PatientIntake {
+            age,
+            gender: fields[1].to_string(),
+            chief_complaint: fields[2].to_string(),
+            location: fields[3].to_string(),
+            triage,
+            vitals,
+        })
+    }
+    
+    /// Triage level to admit with, and whether it must be flagged for review. An explicit
+    /// level always wins; otherwise vitals decide when auto-triage is on, else Medium.
+    fn resolve_triage(&self, auto_triage: bool) -> (TriageLevel, bool) {
+        match (self.triage, &self.vitals) {
+            (Some(level), _) => (level, false),
+            (None, Some(vitals)) if auto_triage => (vitals.overall_triage(), false),
+            (None, _) => (TriageLevel::Medium, true),
+        }
+    }
+}
+
+/// State of the New Patient window.
+#[derive(Debug, Clone)]
+pub struct NewPatientForm {
+    intake: PatientIntake,
+    vitals_taken: bool,
+    draft_vitals: VitalSigns,
+    import_text: String,
+    import_report: Vec<String>,
+}
+
+impl Default for NewPatientForm {
+    fn default() -> Self {
+        Self {
+            intake: PatientIntake {
+                age: 40,
+                gender: String::new(),
+                chief_complaint: String::new(),
+                location: String::new(),
+                triage: None,
+                vitals: None,
+            },
+            vitals_taken: true,
+            draft_vitals: VitalSigns {
+                blood_pressure: (120, 80),
+                heart_rate: 80,
+                oxygen_saturation: 98,
+                temperature: 37.0,
+            },
+            import_text: String::new(),
+            import_report: Vec::new(),
+        }
+    }
+}
+
+/// The first id shared by two patients, if any.
+fn duplicate_patient_id<'a>(patients: impl Iterator<Item = &'a Patient>) -> Option<&'a str> {
+    let mut seen = HashSet::new();
+    patients.map(|p| p.id.as_str()).find(|id| !seen.insert(*id))
+}
+
+/// The record an integrity problem was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueTarget {
+    Patient(String),
+    Hospital(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct IntegrityIssue {
+    target: IssueTarget,
+    message: String,
+}
+
+impl IntegrityIssue {
+    fn patient(patient: &Patient, message: impl Into<String>) -> Self {
+        Self {
+            target: IssueTarget::Patient(patient.id.clone()),
+            message: message.into(),
+        }
+    }
+}
+
+fn check_duplicate_ids(patients: &[Patient], archived: &[Patient]) -> Vec<IntegrityIssue> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    patients
+        .iter()
+        .chain(archived)
+        .filter(|p| !seen.insert(p.id.as_str()) && reported.insert(p.id.as_str()))
+        .map(|p| IntegrityIssue::patient(p, "Id is used by more than one patient record"))
+        .collect()
+}
+
+fn check_inbound_without_ambulance(patients: &[Patient]) -> Vec<IntegrityIssue> {
+    patients
+        .iter()
+        .filter(|p| p.status == PatientStatus::EnRoute && p.eta_minutes.is_some() && p.ambulance_id.is_none())
+        .map(|p| IntegrityIssue::patient(p, "Inbound with an ETA but no ambulance assigned"))
+        .collect()
+}
+
+fn check_unknown_hospitals(patients: &[Patient], hospitals: &[Hospital]) -> Vec<IntegrityIssue> {
+    patients
+        .iter()
+        .filter_map(|p| {
+            let name = p.assigned_hospital.as_ref()?;
+            let known = hospitals.iter().any(|h| &h.name == name);
+            (!known).then(|| IntegrityIssue::patient(p, format!("Assigned to unknown hospital \"{}\"", name)))
+        })
+        .collect()
+}
+
+fn check_bed_counts(patients: &[Patient], hospitals: &[Hospital], rules: &[RoutingRule]) -> Vec<IntegrityIssue> {
+    hospitals
+        .iter()
+        .filter_map(|h| {
+            let message = if h.available_beds > h.total_beds {
+                format!("{} available beds exceeds {} total", h.available_beds, h.total_beds)
+            } else {
+                let projected = h.projected_available(patients, hospitals, rules);
+                if projected >= 0 {
+                    return None;
+                }
+                format!("Projected availability is {} after inbound patients", projected)
+            };
+            Some(IntegrityIssue {
+                target: IssueTarget::Hospital(h.name.clone()),
+                message,
+            })
+        })
+        .collect()
+}
+
+fn check_triage_matches_vitals(patients: &[Patient]) -> Vec<IntegrityIssue> {
+    let rank = |level: TriageLevel| TriageLevel::ALL.iter().position(|l| *l == level).unwrap_or(0);
+    patients
+        .iter()
+        .filter_map(|p| {
+            let vitals_level = p.recorded_vitals()?.overall_triage();
+            (rank(vitals_level) < rank(p.triage_level)).then(|| {
+                IntegrityIssue::patient(p, format!("Triaged {} but vitals read {}", p.triage_level.text(), vitals_level.text()))
+            })
+        })
+        .collect()
+}
+
+fn check_vitals_plausible(patients: &[Patient]) -> Vec<IntegrityIssue> {
+    patients
+        .iter()
+        .flat_map(|p| {
+            let warnings = p.recorded_vitals().map(vitals_sanity_check).unwrap_or_default();
+            warnings.into_iter().map(move |w| IntegrityIssue::patient(p, w.to_string()))
+        })
+        .collect()
+}
+
+/// Runs every `check_*` over the board, the archive and the hospital list.
+fn run_integrity_checks(
+    patients: &[Patient],
+    archived: &[Patient],
+    hospitals: &[Hospital],
+    rules: &[RoutingRule],
+) -> Vec<IntegrityIssue> {
+    let mut issues = check_duplicate_ids(patients, archived);
+    issues.extend(check_inbound_without_ambulance(patients));
+    issues.extend(check_unknown_hospitals(patients, hospitals));
+    issues.extend(check_bed_counts(patients, hospitals, rules));
+    issues.extend(check_triage_matches_vitals(patients));
+    issues.extend(check_vitals_plausible(patients));
+    issues
+}
+
+/// Next free patient id, numbered after the highest `PATIENT-nnn` on the board or in the archive.
+fn next_patient_id<'a>(patients: impl Iterator<Item = &'a Patient>) -> String {
+    let highest = patients
+        .filter_map(|p| p.id.strip_prefix("PATIENT-"))
+        .filter_map(|n| n.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("PATIENT-{:03}", highest + 1)
+}
+
+/// The protocol destination for a patient, if a routing rule matches and it can take them.
+fn protocol_hospital<'a>(patient: &Patient, hospitals: &'a [Hospital], rules: &[RoutingRule]) -> Option<&'a Hospital> {
+    let rule = rules.iter().find(|r| r.matches(patient))?;
+    let mut open = hospitals.iter().filter(|h| h.available_beds > 0 && !h.on_divert);
+    match &rule.route_to {
+        RouteTo::Hospital(name) => open.find(|h| &h.name == name),
+        RouteTo::Capability(capability) => open.filter(|h| h.has(*capability)).min_by_key(|h| h.distance_minutes),
+    }
+}
+
+/// Explains a fallback when the patient's protocol destination is full or on divert.
+fn routing_note(patient: &Patient, hospitals: &[Hospital], rules: &[RoutingRule]) -> Option<String> {
+    if patient.assigned_hospital.is_some() {
+        return None;
+    }
+    let rule = rules.iter().find(|r| r.matches(patient))?;
+    if protocol_hospital(patient, hospitals, rules).is_some() {
+        return None;
+    }
+    Some(format!(
+        "Protocol \"{}\" prefers {}, which cannot accept; routed by general logic",
+        rule.keyword,
+        rule.route_to.text()
+    ))
+}
+
+/// Recommends the protocol destination from `rules` if one matches and can accept. Otherwise
+/// the nearest hospital with an available bed that is not on divert, preferring one that has
+/// every capability the patient needs, and falling back to the nearest open hospital when
+/// none does, so a patient is never left without a destination.
+fn recommend_hospital<'a>(patient: &Patient, hospitals: &'a [Hospital], rules: &[RoutingRule]) -> Option<&'a Hospital> {
+    if let Some(hospital) = protocol_hospital(patient, hospitals, rules) {
+        return Some(hospital);
+    }
+    
+    let needs = patient.needed_capabilities();
+    let open = || hospitals.iter().filter(|h| h.available_beds > 0 && !h.on_divert);
+    
+    open()
+        .filter(|h| needs.iter().all(|&c| h.has(c)))
+        .min_by_key(|h| h.distance_minutes)
+        .or_else(|| open().min_by_key(|h| h.distance_minutes))
+}
+
+/// The assigned hospital of an inbound patient, if it has gone on divert or filled up.
+/// Unassigned patients re-route on their own.
+fn unavailable_destination<'a>(patient: &Patient, hospitals: &'a [Hospital]) -> Option<&'a Hospital> {
+    if patient.status != PatientStatus::EnRoute {
+        return None;
+    }
+    let assigned = patient.assigned_hospital.as_ref()?;
+    hospitals
+        .iter()
+        .find(|h| &h.name == assigned)
+        .filter(|h| h.on_divert || h.available_beds == 0)
+}
+
+/// The next best destination from `recommend_hospital` when `unavailable_destination` applies.
+fn reroute_suggestion<'a>(patient: &Patient, hospitals: &'a [Hospital], rules: &[RoutingRule]) -> Option<&'a Hospital> {
+    let current = unavailable_destination(patient, hospitals)?;
+    recommend_hospital(patient, hospitals, rules).filter(|h| h.name != current.name)
+}
+
+/// Next free unit id, numbered after the highest `AMB-DXB-nnn` already assigned.
+fn next_ambulance_id<'a>(patients: impl Iterator<Item = &'a Patient>) -> String {
+    let highest = patients
+        .filter_map(|p| p.ambulance_id.as_deref())
+        .filter_map(|id| id.strip_prefix("AMB-DXB-"))
+        .filter_map(|n| n.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("AMB-DXB-{:03}", highest + 1)
+}
+
+/// Seconds between automatic vitals samples.
+const VITALS_SAMPLE_INTERVAL_SECS: i64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertKind {
+    Critical,
+    Warning,
+    Info,
+}
+
+impl AlertKind {
+    const ALL: [AlertKind; 3] = [AlertKind::Critical, AlertKind::Warning, AlertKind::Info];
+    
+    fn text(&self) -> &str {
+        match self {
+            AlertKind::Critical => "Critical",
+            AlertKind::Warning => "Warning",
+            AlertKind::Info => "Info",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoundPack {
+    SubtleChimes,
+    Klaxon,
+}
+
+impl SoundPack {
+    const ALL: [SoundPack; 2] = [SoundPack::SubtleChimes, SoundPack::Klaxon];
+    
+    fn text(&self) -> &str {
+        match self {
+            SoundPack::SubtleChimes => "Subtle chimes",
+            SoundPack::Klaxon => "Klaxon",
+        }
+    }
+    
+    fn sound_name(&self, kind: AlertKind) -> &'static str {
+        match (self, kind) {
+            (SoundPack::SubtleChimes, AlertKind::Critical) => "chime-triple",
+            (SoundPack::SubtleChimes, AlertKind::Warning) => "chime-double",
+            (SoundPack::SubtleChimes, AlertKind::Info) => "chime-single",
+            (SoundPack::Klaxon, AlertKind::Critical) => "klaxon-long",
+            (SoundPack::Klaxon, AlertKind::Warning) => "klaxon-short",
+            (SoundPack::Klaxon, AlertKind::Info) => "beep",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    muted: bool,
+    sound_pack: SoundPack,
+    master_volume: f32,
+    /// Per-kind volume, indexed in `AlertKind::ALL` order.
+    kind_volume: [f32; 3],
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            sound_pack: SoundPack::SubtleChimes,
+            master_volume: 0.7,
+            kind_volume: [1.0, 0.7, 0.4],
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Resolves what to play for an alert, or `None` when muted or silent.
+    fn cue(&self, kind: AlertKind) -> Option<AlertCue> {
+        let index = AlertKind::ALL.iter().position(|k| *k == kind).unwrap_or(0);
+        let volume = self.master_volume * self.kind_volume[index];
+        if self.muted || volume <= 0.0 {
+            return None;
+        }
+        
+        Some(AlertCue {
+            kind,
+            sound: self.sound_pack.sound_name(kind),
+            volume,
+            at: Local::now(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertCue {
+    kind: AlertKind,
+    sound: &'static str,
+    volume: f32,
+    at: DateTime<Local>,
+}
+
+/// Destination for alert cues. No audio device backend is linked yet, so cues are only
+/// recorded for display; a real backend only needs to implement `play`.
+#[derive(Debug, Default)]
+pub struct AudioOutput {
+    last_cue: Option<AlertCue>,
+}
+
+impl AudioOutput {
+    fn play(&mut self, cue: AlertCue) {
+        self.last_cue = Some(cue);
+    }
+}
+
+/// What keeps a notification alive. Conditions are re-checked every frame and clear
+/// themselves once they stop holding; manual alerts stay until dismissed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertSource {
+    StaleTelemetry(String),
+    HospitalUnavailable(String),
+    Manual,
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    kind: AlertKind,
+    source: AlertSource,
+    message: String,
+    raised_at: DateTime<Local>,
+    resolved_at: Option<DateTime<Local>>,
+}
+
+/// How long a resolved alert stays visible, fading out, before it is auto-dismissed.
+const RESOLVED_ALERT_FADE_SECS: f32 = 4.0;
+/// Most resolved alerts kept for review when auto-dismiss is off; older ones are dropped.
+const MAX_RESOLVED_ALERTS: usize = 20;
+
+/// Conditions that currently warrant a notification.
+fn alert_conditions(
+    patients: &[Patient],
+    hospitals: &[Hospital],
+    now: DateTime<Local>,
+    stale_after_secs: i64,
+) -> Vec<(AlertSource, AlertKind, String)> {
+    let stale = patients
+        .iter()
+        .filter(|p| p.eta_countdown(now, stale_after_secs).is_some_and(|c| c.stale))
+        .map(|p| {
+            (
+                AlertSource::StaleTelemetry(p.id.clone()),
+                AlertKind::Warning,
+                format!("No telemetry from {} for over {} min", p.id, stale_after_secs / 60),
+            )
+        });
+    let unavailable = hospitals
+        .iter()
+        .filter(|h| h.on_divert || h.available_beds == 0)
+        .map(|h| {
+            let reason = if h.on_divert { "is on divert" } else { "has no beds available" };
+            (
+                AlertSource::HospitalUnavailable(h.name.clone()),
+                AlertKind::Warning,
+                format!("{} {}", h.name, reason),
+            )
+        });
+    stale.chain(unavailable).collect()
+}
+
+/// Raises alerts for new conditions, marks alerts whose condition no longer holds as
+/// resolved and, when `auto_dismiss` is on, drops them once their fade has finished.
+/// Otherwise only the newest `MAX_RESOLVED_ALERTS` resolved alerts are kept.
+fn sync_alerts(
+    alerts: &mut Vec<Alert>,
+    conditions: Vec<(AlertSource, AlertKind, String)>,
+    now: DateTime<Local>,
+    auto_dismiss: bool,
+) {
+    for alert in alerts.iter_mut() {
+        if alert.source != AlertSource::Manual
+            && alert.resolved_at.is_none()
+            && !conditions.iter().any(|(source, _, _)| *source == alert.source)
+        {
+            alert.resolved_at = Some(now);
+        }
+    }
+    
+    for (source, kind, message) in conditions {
+        if !alerts.iter().any(|a| a.source == source && a.resolved_at.is_none()) {
+            alerts.push(Alert {
+                kind,
+                source,
+                message,
+                raised_at: now,
+                resolved_at: None,
+            });
+        }
+    }
+    
+    if auto_dismiss {
+        alerts.retain(|a| {
+            a.resolved_at
+                .is_none_or(|at| ((now - at).num_milliseconds() as f32 / 1000.0) < RESOLVED_ALERT_FADE_SECS)
+        });
+    } else {
+        let resolved = alerts.iter().filter(|a| a.resolved_at.is_some()).count();
+        let mut excess = resolved.saturating_sub(MAX_RESOLVED_ALERTS);
+        alerts.retain(|a| {
+            let drop = excess > 0 && a.resolved_at.is_some();
+            if drop {
+                excess -= 1;
+            }
+            !drop
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    vitals_retention: VitalsRetention,
+    handover_checklist_enabled: bool,
+    handover_checklist_items: Vec<String>,
+    telemetry_stale_secs: i64,
+    reassess_enabled: bool,
+    /// Minutes before re-triage is due, indexed in `TriageLevel::ALL` order.
+    reassess_minutes: [i64; 4],
+    reduce_motion: bool,
+    show_route_connector: bool,
+    audio: AudioSettings,
+    auto_archive_enabled: bool,
+    /// Minutes after discharge or transfer before a patient is archived.
+    archive_after_minutes: i64,
+    /// Derive triage from vitals when a new patient has none given.
+    auto_triage: bool,
+    /// Chat senders are shown online for this long after their last message...
+    presence_online_minutes: i64,
+    /// ...and away until this long, after which they drop off the presence list.
+    presence_away_minutes: i64,
+    /// Switch to a dim summary-only screen after a period without input.
+    idle_dim_enabled: bool,
+    idle_dim_minutes: i64,
+    /// Remove notifications whose condition has cleared, after a short fade.
+    auto_dismiss_resolved_alerts: bool,
+    /// Show render timings for the sidebar, for checking performance on ward hardware.
+    show_frame_timing: bool,
+    /// Run the data integrity checks after each CSV import.
+    integrity_check_on_import: bool,
+    /// Slow the once-a-second repaint while no app window has focus.
+    throttle_when_unfocused: bool,
+    unfocused_repaint_secs: u64,
+    /// Complaint-based routing protocols, checked in order.
+    routing_rules: Vec<RoutingRule>,
+    escalation_routes: Vec<EscalationRoute>,
+    /// Which actions show as buttons on patient cards, and in what order.
+    card_actions: Vec<CardActionSlot>,
+    locale: LocaleSettings,
+    group_by: GroupBy,
+}
+
+impl Settings {
+    fn reassess_window(&self, level: TriageLevel) -> i64 {
+        let index = TriageLevel::ALL.iter().position(|l| *l == level).unwrap_or(0);
+        self.reassess_minutes[index]
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            vitals_retention: VitalsRetention::LastSamples(360),
+            handover_checklist_enabled: true,
+            handover_checklist_items: vec![
+                "Airway secured".to_string(),
+                "IV access".to_string(),
+                "Allergies confirmed".to_string(),
+                "ID verified".to_string(),
+            ],
+            telemetry_stale_secs: 120,
+            reassess_enabled: true,
+            reassess_minutes: [15, 20, 30, 60],
+            reduce_motion: false,
+            show_route_connector: true,
+            audio: AudioSettings::default(),
+            auto_archive_enabled: true,
+            archive_after_minutes: 30,
+            auto_triage: true,
+            presence_online_minutes: 5,
+            presence_away_minutes: 30,
+            idle_dim_enabled: false,
+            idle_dim_minutes: 10,
+            auto_dismiss_resolved_alerts: true,
+            show_frame_timing: false,
+            integrity_check_on_import: true,
+            throttle_when_unfocused: false,
+            unfocused_repaint_secs: 15,
+            routing_rules: vec![
+                RoutingRule {
+                    keyword: "stroke".to_string(),
+                    route_to: RouteTo::Hospital("Rashid Hospital".to_string()),
+                },
+                RoutingRule {
+                    keyword: "burn".to_string(),
+                    route_to: RouteTo::Capability(Capability::Burns),
+                },
+            ],
+            escalation_routes: vec![
+                EscalationRoute {
+                    event: EscalationEvent::NewCritical,
+                    mention: StaffRole::ChargeNurse,
+                },
+                EscalationRoute {
+                    event: EscalationEvent::CodeCalled,
+                    mention: StaffRole::ChargeNurse,
+                },
+            ],
+            card_actions: card_action_preset(StaffRole::ChargeNurse),
+            locale: LocaleSettings::default(),
+            group_by: GroupBy::None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmergencyApp {
+    patients: Vec<Patient>,
+    /// Discharged/transferred patients moved off the active board, full record retained.
+    archived_patients: Vec<Patient>,
+    show_archive: bool,
+    board_view: BoardView,
+    /// "Jump to most urgent" considers patients hidden by the current filters.
+    urgent_ignores_filters: bool,
+    hospitals: Vec<Hospital>,
+    specialists: Vec<Specialist>,
+    chat_messages: Vec<ChatMessage>,
+    active_tab: usize,
+    chat_input: String,
+    /// Chat is shown in its own OS window instead of the right-hand panel.
+    chat_popped_out: bool,
+    /// Attach the selected patient's vitals to the next chat message.
+    attach_vitals: bool,
+    /// Id of the selected patient; ids stay stable across sorting, filtering and removal.
+    selected_patient: Option<String>,
+    ambulance_available: u32,
+    ambulance_en_route: u32,
+    ambulance_at_scene: u32,
+    zone_rules: Vec<ZoneRule>,
+    new_zone_keyword: String,
+    new_zone_name: String,
+    settings: Settings,
+    show_settings: bool,
+    show_ranges_legend: bool,
+    /// Patient ids kept above the board, in pin order. Session only.
+    pinned_patients: Vec<String>,
+    /// Last integrity check: when it ran and what it found.
+    integrity_report: Option<(DateTime<Local>, Vec<IntegrityIssue>)>,
+    show_integrity: bool,
+    alerts: Vec<Alert>,
+    show_notifications: bool,
+    manual_alert_draft: String,
+    last_vitals_sample: DateTime<Local>,
+    pending_handover: Option<PendingHandover>,
+    pending_capacity_override: Option<PendingOverride>,
+    new_checklist_item: String,
+    analytics_range: AnalyticsRange,
+    /// Outcome of the last shift report export.
+    export_status: Option<String>,
+    patient_filter: PatientFilter,
+    /// Patient id and proposed level for an open re-assessment dialog.
+    pending_reassessment: Option<(String, TriageLevel)>,
+    /// Patient card under the pointer, used to highlight its destination hospital.
+    hovered_patient: Option<String>,
+    /// Screen rects from the current frame, used to draw the route connector.
+    hospital_rects: HashMap<String, egui::Rect>,
+    sidebar_cache: SidebarCache,
+    /// Set whenever hospitals, specialists, routing rules or a patient change so the sidebar
+    /// rebuilds its rows and re-routes the focused patient.
+    sidebar_dirty: bool,
+    sidebar_timing: FrameTiming,
+    patient_card_rects: HashMap<String, egui::Rect>,
+    current_user: StaffMember,
+    broadcast: Option<Broadcast>,
+    incident: Option<Incident>,
+    show_broadcast_composer: bool,
+    broadcast_input: String,
+    broadcast_severity: BroadcastSeverity,
+    vitals_editor: Option<VitalsEditor>,
+    new_patient_form: Option<NewPatientForm>,
+    hospital_filter: HospitalFilter,
+    show_patient_details: bool,
+    /// Unsaved in-hospital location edits in the details window, by patient id.
+    location_draft: Option<(String, InHospitalLocation)>,
+    order_draft: String,
+    show_bay_board: bool,
+    audio: AudioOutput,
+    /// Unsent field note text per patient id.
+    field_note_drafts: HashMap<String, String>,
+    /// Set when a selection should scroll its card into view on the next render.
+    scroll_to_selected: bool,
+    /// Explains why filters were cleared to reveal a selected patient.
+    selection_notice: Option<String>,
+    /// Failed actions shown in the banner stack, oldest first.
+    errors: Vec<(DateTime<Local>, AppError)>,
+    /// Last pointer or keyboard activity, for the idle dim screen.
+    last_input_at: DateTime<Local>,
+    /// Critical patients already on the board; a new one wakes the idle screen. `None` until
+    /// the first frame so patients present at startup don't trigger an alert.
+    seen_critical: Option<HashSet<String>>,
+    /// When each patient was last seen needing attention; `None` until the first frame.
+    seen_attention: Option<HashMap<String, DateTime<Local>>>,
+    /// Issue time of the broadcast shown last frame, so a new Critical one wakes the idle screen.
+    seen_broadcast: Option<DateTime<Local>>,
+}
+
+/// A failed action, reported through the banner stack at the top of the main panel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// Saved state was unreadable and demo data is shown instead.
+    StateLoad(String),
+    /// Some CSV import lines could not be parsed; the form's import report lists them.
+    Import { failed: usize, total: usize },
+    FileWrite { path: String, message: String },
+    /// A patient was accepted into a hospital with no free beds.
+    OverCapacity { patient_id: String, hospital: String },
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::StateLoad(reason) => {
+                write!(f, "Saved state could not be loaded ({}). Showing demo data instead.", reason)
+            }
+            AppError::Import { failed, total } => {
+                write!(f, "{} of {} import line(s) could not be read; see the import report", failed, total)
+            }
+            AppError::FileWrite { path, message } => write!(f, "Could not write {}: {}", path, message),
+            AppError::OverCapacity { patient_id, hospital } => {
+                write!(f, "{} accepted into {} over capacity", patient_id, hospital)
+            }
+        }
+    }
+}
+
+impl AppError {
+    /// Seconds before the banner dismisses itself; `None` stays until dismissed.
+    fn expires_after_secs(&self) -> Option<i64> {
+        match self {
+            AppError::StateLoad(_) => None,
+            AppError::Import { .. } | AppError::FileWrite { .. } | AppError::OverCapacity { .. } => Some(30),
+        }
+    }
+}
+
+/// Most error banners shown at once; older ones that expire anyway are dropped first.
+const MAX_ERROR_BANNERS: usize = 5;
+
+/// Storage key for the persisted board state.
+const STATE_KEY: &str = "emergency_state";
+
+/// Version of the persisted `SavedState` layout. Bump this when the schema changes and
+/// add a step to `SavedState::migrate`. Fields added after a version ships must carry
+/// `#[serde(default)]` (or be covered by a migration) so older saves still deserialize.
+const STATE_VERSION: u32 = 3;
+
+/// Only the version tag, read first so a save can be routed to the right migration.
+#[derive(Deserialize)]
+struct SavedVersion {
+    version: u32,
+}
+
+/// The persisted part of `EmergencyApp`; transient UI state is not saved.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedState {
+    version: u32,
+    patients: Vec<Patient>,
+    #[serde(default)]
+    archived_patients: Vec<Patient>,
+    hospitals: Vec<Hospital>,
+    specialists: Vec<Specialist>,
+    chat_messages: Vec<ChatMessage>,
+    ambulance_available: u32,
+    ambulance_en_route: u32,
+    ambulance_at_scene: u32,
+    zone_rules: Vec<ZoneRule>,
+    settings: Settings,
+    current_user: StaffMember,
+    broadcast: Option<Broadcast>,
+    /// The current or most recent MCI, kept after stand-down for its report.
+    #[serde(default)]
+    incident: Option<Incident>,
+}
+
+impl SavedState {
+    /// Parses a saved blob, upgrading older versions to the current layout.
+    fn load(blob: &str) -> Result<SavedState, String> {
+        let SavedVersion { version } =
+            ron::from_str(blob).map_err(|e| format!("saved state has no readable version: {}", e))?;
+        
+        if version > STATE_VERSION {
+            return Err(format!(
+                "saved state is version {} but this build only understands up to {}",
+                version, STATE_VERSION
+            ));
+        }
+        
+        let mut state: SavedState =
+            ron::from_str(blob).map_err(|e| format!("could not read version {} saved state: {}", version, e))?;
+        state.migrate(version);
+        Ok(state)
+    }
+    
+    /// Applies each upgrade step between `from` and `STATE_VERSION` in order. New fields
+    /// are already defaulted by serde; steps here fix up values that need real derivation.
+    fn migrate(&mut self, from: u32) {
+        debug_assert!(from <= STATE_VERSION);
+        
+        // v2: patient status was introduced; infer it from whether the patient is inbound
+        if from < 2 {
+            for patient in &mut self.patients {
+                patient.status = if patient.eta_minutes.is_some() {
+                    PatientStatus::EnRoute
+                } else {
+                    PatientStatus::InTriage
+                };
+            }
+        }
+        
+        // v3: hospitals gained capability flags; copy them for known hospitals, infer the rest
+        if from < 3 {
+            let known = create_demo_hospitals();
+            for hospital in &mut self.hospitals {
+                match known.iter().find(|k| k.name == hospital.name) {
+                    Some(k) => {
+                        hospital.trauma_center = k.trauma_center;
+                        hospital.stroke_ready = k.stroke_ready;
+                        hospital.cath_lab = k.cath_lab;
+                        hospital.pediatric = k.pediatric;
+                        hospital.burn_unit = k.burn_unit;
+                    }
+                    None => hospital.infer_capabilities(),
+                }
+            }
+        }
+        
+        self.version = STATE_VERSION;
+    }
+}
+
+impl EmergencyApp {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        
+        let Some(blob) = cc.storage.and_then(|storage| storage.get_string(STATE_KEY)) else {
+            return app;
+        };
+        
+        match SavedState::load(&blob) {
+            Ok(state) => app.restore(state),
+            Err(error) => {
+                app.push_error(AppError::StateLoad(error));
+            }
+        }
+        
+        app
+    }
+    
+    fn restore(&mut self, state: SavedState) {
+        self.patients = state.patients;
+        self.archived_patients = state.archived_patients;
+        self.hospitals = state.hospitals;
+        self.specialists = state.specialists;
+        self.sidebar_dirty = true;
+        self.chat_messages = state.chat_messages;
+        self.ambulance_available = state.ambulance_available;
+        self.ambulance_en_route = state.ambulance_en_route;
+        self.ambulance_at_scene = state.ambulance_at_scene;
+        self.zone_rules = state.zone_rules;
+        self.settings = state.settings;
+        self.current_user = state.current_user;
+        self.broadcast = state.broadcast;
+        self.incident = state.incident;
+    }
+    
+    fn saved_state(&self) -> SavedState {
+        SavedState {
+            version: STATE_VERSION,
+            patients: self.patients.clone(),
+            archived_patients: self.archived_patients.clone(),
+            hospitals: self.hospitals.clone(),
+            specialists: self.specialists.clone(),
+            chat_messages: self.chat_messages.clone(),
+            ambulance_available: self.ambulance_available,
+            ambulance_en_route: self.ambulance_en_route,
+            ambulance_at_scene: self.ambulance_at_scene,
+            zone_rules: self.zone_rules.clone(),
+            settings: self.settings.clone(),
+            current_user: self.current_user.clone(),
+            broadcast: self.broadcast.clone(),
+            incident: self.incident.clone(),
+        }
+    }
+}
+
+impl Default for EmergencyApp {
+    fn default() -> Self {
+        Self {
+            patients: create_demo_patients(),
+            archived_patients: vec![],
+            show_archive: false,
+            board_view: BoardView::Cards,
+            urgent_ignores_filters: false,
+            hospitals: create_demo_hospitals(),
+            specialists: create_demo_specialists(),
+            chat_messages: create_demo_messages(),
+            active_tab: 0,
+            chat_input: String::new(),
+            chat_popped_out: false,
+            attach_vitals: false,
+            selected_patient: None,
+            ambulance_available: 12,
+            ambulance_en_route: 8,
+            ambulance_at_scene: 3,
+            zone_rules: create_default_zone_rules(),
+            new_zone_keyword: String::new(),
+            new_zone_name: String::new(),
+            settings: Settings::default(),
+            show_settings: false,
+            show_ranges_legend: false,
+            pinned_patients: Vec::new(),
+            integrity_report: None,
+            show_integrity: false,
+            alerts: Vec::new(),
+            show_notifications: false,
+            manual_alert_draft: String::new(),
+            last_vitals_sample: Local::now(),
+            pending_handover: None,
+            pending_capacity_override: None,
+            new_checklist_item: String::new(),
+            analytics_range: AnalyticsRange::LastHour,
+            export_status: None,
+            patient_filter: PatientFilter::default(),
+            pending_reassessment: None,
+            hovered_patient: None,
+            hospital_rects: HashMap::new(),
+            sidebar_cache: SidebarCache::default(),
+            sidebar_dirty: true,
+            sidebar_timing: FrameTiming::default(),
+            patient_card_rects: HashMap::new(),
+            current_user: StaffMember {
+                name: "Dr. Ahmed Al-Mansoori".to_string(),
+                role: StaffRole::ErDirector,
+            },
+            broadcast: None,
+            incident: None,
+            show_broadcast_composer: false,
+            broadcast_input: String::new(),
+            broadcast_severity: BroadcastSeverity::Critical,
+            vitals_editor: None,
+            new_patient_form: None,
+            hospital_filter: HospitalFilter::default(),
+            show_patient_details: false,
+            location_draft: None,
+            order_draft: String::new(),
+            show_bay_board: false,
+            audio: AudioOutput::default(),
+            field_note_drafts: HashMap::new(),
+            scroll_to_selected: false,
+            selection_notice: None,
+            errors: Vec::new(),
+            last_input_at: Local::now(),
+            seen_critical: None,
+            seen_attention: None,
+            seen_broadcast: None,
+        }
+    }
+}
+
+impl eframe::App for EmergencyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if let Ok(blob) = ron::to_string(&self.saved_state()) {
+            storage.set_string(STATE_KEY, blob);
+        }
+    }
+    
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Configure fonts and style
+        self.configure_fonts(ctx);
+        
+        // Set dark theme
+        ctx.set_visuals(egui::Visuals::dark());
+        
+        // Request repaint every second for real-time updates, less often when nobody is looking.
+        // Focus changes and other input repaint immediately on their own.
+        let focused = ctx.input(|i| i.raw.viewports.values().any(|v| v.focused.unwrap_or(true)));
+        let repaint_secs = if self.settings.throttle_when_unfocused && !focused {
+            self.settings.unfocused_repaint_secs
+        } else {
+            1
+        };
+        ctx.request_repaint_after(std::time::Duration::from_secs(repaint_secs));
+        
+        self.sample_vitals();
+        self.archive_finished_patients();
+        self.update_alerts();
+        self.resolve_selection();
+        debug_assert!(
+            duplicate_patient_id(self.patients.iter().chain(self.archived_patients.iter())).is_none(),
+            "patient ids must be unique; widget state is keyed on them"
+        );
+        
+        let new_critical = self.track_critical_arrivals();
+        for id in &new_critical {
+            self.post_escalation(EscalationEvent::NewCritical, format!("{} is now CRITICAL", id));
+        }
+        for id in self.track_attention_changes() {
+            self.post_escalation(EscalationEvent::NeedsAttention, format!("{} needs attention", id));
+        }
+        let critical_broadcast = self.track_critical_broadcast();
+        let new_critical = !new_critical.is_empty() || critical_broadcast;
+        let idle = self.update_idle(ctx);
+        
+        // Critical arrivals and broadcasts always break through the dim screen and wake an unfocused window
+        if new_critical && (idle || !focused) {
+            self.last_input_at = Local::now();
+            self.play_alert(AlertKind::Critical);
+            if !focused {
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Critical));
+            }
+        } else if idle {
+            self.render_idle_summary(ctx);
+            if self.chat_popped_out {
+                self.render_chat_viewport(ctx);
+            }
+            return;
+        }
+        
+        // Broadcast banner sits above everything, including the header
+        self.render_broadcast_panel(ctx);
+        
+        // Header
+        TopBottomPanel::top("header").show(ctx, |ui| {
+            self.render_header(ui);
+        });
+        
+        TopBottomPanel::top("triage_strip").show(ctx, |ui| {
+            self.render_triage_strip(ui);
+        });
+        
+        // Left sidebar
+        SidePanel::left("sidebar").min_width(280.0).show(ctx, |ui| {
+            self.render_sidebar(ui);
+        });
+        
+        // Right chat panel, unless it has been popped out to its own window
+        if self.chat_popped_out {
+            self.render_chat_viewport(ctx);
+        } else {
+            SidePanel::right("chat").min_width(300.0).show(ctx, |ui| {
+                self.render_chat_panel(ui);
+            });
+        }
+        
+        // Main content area
+        CentralPanel::default().show(ctx, |ui| {
+            self.render_main_content(ui);
+        });
+        
+        self.render_route_connector(ctx);
+        self.render_settings_window(ctx);
+        self.render_ranges_legend(ctx);
+        self.render_notifications_window(ctx);
+        self.render_integrity_window(ctx);
+        self.render_capacity_override_window(ctx);
+        self.render_handover_window(ctx);
+        self.render_reassessment_window(ctx);
+        self.render_broadcast_composer(ctx);
+        self.render_vitals_editor(ctx);
+        self.render_new_patient_window(ctx);
+        self.render_patient_details(ctx);
+    }
+}
+
+impl EmergencyApp {
+    fn configure_fonts(&self, ctx: &Context) {
+        // Using default fonts for now - in production you can add custom fonts
+        let fonts = egui::FontDefinitions::default();
+        ctx.set_fonts(fonts);
+    }
+    
+    fn sample_vitals(&mut self) {
+        let now = Local::now();
+        if (now - self.last_vitals_sample).num_seconds() < VITALS_SAMPLE_INTERVAL_SECS {
+            return;
+        }
+        
+        let retention = self.settings.vitals_retention;
+        for patient in self.patients.iter_mut().filter(|p| !p.awaiting_vitals) {
+            patient.record_vitals(retention, now);
+        }
+        self.last_vitals_sample = now;
+    }
+    
+    fn selected_patient(&self) -> Option<&Patient> {
+        let id = self.selected_patient.as_ref()?;
+        self.patients.iter().find(|p| &p.id == id)
+    }
+    
+    /// Moves discharged/transferred patients to the archive once the configured delay passes.
+    fn archive_finished_patients(&mut self) {
+        if !self.settings.auto_archive_enabled {
+            return;
+        }
+        
+        let cutoff = Local::now() - chrono::Duration::minutes(self.settings.archive_after_minutes);
+        let (mut finished, active): (Vec<Patient>, Vec<Patient>) = std::mem::take(&mut self.patients)
+            .into_iter()
+            .partition(|p| p.status.is_finished() && p.status_changed_at.is_some_and(|at| at <= cutoff));
+        
+        self.patients = active;
+        let ids: Vec<String> = finished.iter().map(|p| p.id.clone()).collect();
+        for patient in &mut finished {
+            patient.log_event("Archived");
+        }
+        self.archived_patients.extend(finished);
+        for id in ids {
+            self.release_ambulance(&id);
+        }
+    }
+    
+    fn restore_archived(&mut self, patient_id: &str) {
+        let Some(index) = self.archived_patients.iter().position(|p| p.id == patient_id) else {
+            return;
+        };
+        
+        let mut patient = self.archived_patients.remove(index);
+        patient.log_event("Restored from archive");
+        patient.set_status(PatientStatus::InTreatment);
+        self.patients.push(patient);
+    }
+    
+    /// Tracks input; returns whether the dim summary should show.
+    fn update_idle(&mut self, ctx: &Context) -> bool {
+        let now = Local::now();
+        if ctx.input(|i| !i.events.is_empty()) {
+            self.last_input_at = now;
+        }
+        
+        self.settings.idle_dim_enabled
+            && (now - self.last_input_at).num_minutes() >= self.settings.idle_dim_minutes
+    }
+    
+    /// Whether a Critical broadcast has been issued since the previous frame.
+    fn track_critical_broadcast(&mut self) -> bool {
+        let current = self.broadcast.as_ref().map(|b| b.issued_at);
+        let is_new = current.is_some() && current != self.seen_broadcast;
+        self.seen_broadcast = current;
+        is_new && self.broadcast.as_ref().is_some_and(|b| b.severity == BroadcastSeverity::Critical)
+    }
+    
+    /// Patients that have become Critical since the previous frame.
+    fn track_critical_arrivals(&mut self) -> Vec<String> {
+        let critical: HashSet<String> = self
+            .patients
+            .iter()
+            .filter(|p| p.triage_level == TriageLevel::Critical)
+            .map(|p| p.id.clone())
+            .collect();
+        let new_critical = match &self.seen_critical {
+            Some(seen) => critical.difference(seen).cloned().collect(),
+            None => Vec::new(),
+        };
+        self.seen_critical = Some(critical);
+        new_critical
+    }
+    
+    /// Patients that have started needing attention after being clear for a while.
+    fn track_attention_changes(&mut self) -> Vec<String> {
+        let now = Local::now();
+        let attention: Vec<String> = self
+            .patients
+            .iter()
+            .filter(|p| p.needs_attention(now, &self.settings))
+            .map(|p| p.id.clone())
+            .collect();
+        match &mut self.seen_attention {
+            Some(seen) => debounce_attention(seen, &attention, now),
+            None => {
+                self.seen_attention = Some(attention.into_iter().map(|id| (id, now)).collect());
+                Vec::new()
+            }
+        }
+    }
+    
+    /// Posts an automated message to the team chat, mentioning any roles routed for `event`.
+    fn post_escalation(&mut self, event: EscalationEvent, text: String) {
+        self.chat_messages.push(ChatMessage {
+            id: Uuid::new_v4(),
+            sender: "Escalation".to_string(),
+            message: text,
+            timestamp: Local::now(),
+            urgent: event != EscalationEvent::NeedsAttention,
+            attachment: None,
+            mentions: escalation_mentions(&self.settings.escalation_routes, event),
+            automated: true,
+        });
+    }
+    
+    /// Minimal, low-brightness board for an idle wall display. Any input restores the full view.
+    fn render_idle_summary(&mut self, ctx: &Context) {
+        let dim = Color32::from_gray(110);
+        
+        // Facility broadcasts stay up at full brightness
+        self.render_broadcast_panel(ctx);
+        
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(Color32::BLACK))
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() * 0.3);
+                    
+                    ui.label(
+                        RichText::new(self.settings.locale.time(Local::now(), false))
+                            .font(FontId::new(64.0, FontFamily::Proportional))
+                            .color(dim)
+                    );
+                    
+                    if let Some(incident) = self.incident.as_ref().filter(|i| i.is_active()) {
+                        ui.label(
+                            RichText::new(self.settings.locale.digits(&format!("⏱ MCI {}", format_hh_mm_ss(incident.elapsed_secs(Local::now())))))
+                                .font(FontId::new(24.0, FontFamily::Monospace))
+                                .color(Color32::from_rgb(150, 50, 40))
+                        );
+                    }
+                    
+                    ui.add_space(20.0);
+                    
+                    ui.horizontal(|ui| {
+                        // Center the row of counts
+                        let width = TriageLevel::ALL.len() as f32 * 140.0;
+                        ui.add_space(((ui.available_width() - width) / 2.0).max(0.0));
+                        for level in TriageLevel::ALL {
+                            let count = self.patients.iter().filter(|p| p.triage_level == level).count();
+                            ui.vertical(|ui| {
+                                ui.set_width(140.0);
+                                ui.vertical_centered(|ui| {
+                                    ui.label(
+                                        RichText::new(count.to_string())
+                                            .font(FontId::new(36.0, FontFamily::Proportional))
+                                            .color(level.color().linear_multiply(0.5))
+                                            .strong()
+                                    );
+                                    ui.label(RichText::new(level.text()).color(dim));
+                                });
+                            });
+                        }
+                    });
+                    
+                    ui.add_space(30.0);
+                    ui.label(RichText::new("Move the mouse or press any key to wake").color(Color32::from_gray(70)));
+                });
+            });
+    }
+    
+    /// Plays the configured sound for an alert. All audible alerts go through here.
+    fn play_alert(&mut self, kind: AlertKind) {
+        if let Some(cue) = self.settings.audio.cue(kind) {
+            self.audio.play(cue);
+        }
+    }
+    
+    /// Selects a patient and scrolls its card into view.
+    fn select_patient(&mut self, patient_id: &str) {
+        self.selected_patient = Some(patient_id.to_string());
+        self.scroll_to_selected = true;
+    }
+    
+    /// Clears the selection once the selected patient no longer exists.
+    fn resolve_selection(&mut self) {
+        if self.selected_patient.is_some() && self.selected_patient().is_none() {
+            self.selected_patient = None;
+        }
+    }
+    
+    /// The patient whose destination should be highlighted: the hovered card, else the selection.
+    fn focused_patient(&self) -> Option<&Patient> {
+        let id = self.hovered_patient.as_ref().or(self.selected_patient.as_ref())?;
+        self.patients.iter().find(|p| &p.id == id)
+    }
+    
+    fn render_route_connector(&self, ctx: &Context) {
+        if !self.settings.show_route_connector {
+            return;
+        }
+        let Some(patient) = self.focused_patient() else {
+            return;
+        };
+        let Some(hospital) = patient.destination(&self.hospitals, &self.settings.routing_rules) else {
+            return;
+        };
+        let (Some(card), Some(target)) = (
+            self.patient_card_rects.get(&patient.id),
+            self.hospital_rects.get(&hospital.name),
+        ) else {
+            return;
+        };
+        
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("route_connector")));
+        let from = card.left_center();
+        let to = target.right_center();
+        let color = Color32::from_rgba_unmultiplied(52, 152, 219, 180);
+        
+        painter.line_segment([from, to], Stroke::new(2.0, color));
+        painter.circle_filled(from, 4.0, color);
+        painter.circle_filled(to, 4.0, color);
+    }
+    
+    fn run_integrity_check(&mut self) {
+        let issues = run_integrity_checks(
+            &self.patients,
+            &self.archived_patients,
+            &self.hospitals,
+            &self.settings.routing_rules,
+        );
+        self.integrity_report = Some((Local::now(), issues));
+    }
+    
+    fn render_integrity_window(&mut self, ctx: &Context) {
+        if !self.show_integrity {
+            return;
+        }
+        
+        let mut open = true;
+        let mut run = false;
+        let mut clicked = None;
+        
+        egui::Window::new("🩺 Data Integrity")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Run checks").clicked() {
+                        run = true;
+                    }
+                    ui.checkbox(&mut self.settings.integrity_check_on_import, "Run after CSV import");
+                });
+                ui.separator();
+                
+                let Some((at, issues)) = &self.integrity_report else {
+                    ui.label(RichText::new("Not run yet").color(Color32::GRAY));
+                    return;
+                };
+                
+                let summary = if issues.is_empty() {
+                    RichText::new(format!("✔ No problems found at {}", self.settings.locale.time(*at, true)))
+                        .color(Color32::from_rgb(46, 204, 113))
+                } else {
+                    RichText::new(format!("⚠ {} problem(s) found at {}", issues.len(), self.settings.locale.time(*at, true)))
+                        .color(Color32::from_rgb(243, 156, 18))
+                };
+                ui.label(summary.strong());
+                
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    egui::Grid::new("integrity_issues").num_columns(2).striped(true).show(ui, |ui| {
+                        for issue in issues {
+                            let name = match &issue.target {
+                                IssueTarget::Patient(id) => format!("👤 {}", id),
+                                IssueTarget::Hospital(name) => format!("🏥 {}", name),
+                            };
+                            if ui.link(name).clicked() {
+                                clicked = Some(issue.target.clone());
+                            }
+                            ui.label(&issue.message);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        
+        self.show_integrity = open;
+        if run {
+            self.run_integrity_check();
+        }
+        match clicked {
+            Some(IssueTarget::Patient(id)) => {
+                if self.patients.iter().any(|p| p.id == id) {
+                    self.active_tab = 0;
+                    self.show_archive = false;
+                    self.select_patient(&id);
+                    self.show_patient_details = true;
+                } else {
+                    self.active_tab = 0;
+                    self.show_archive = true;
+                }
+            }
+            Some(IssueTarget::Hospital(_)) => {
+                self.active_tab = 2;
+            }
+            None => {}
+        }
+    }
+    
+    fn update_alerts(&mut self) {
+        let now = Local::now();
+        let conditions = alert_conditions(&self.patients, &self.hospitals, now, self.settings.telemetry_stale_secs);
+        sync_alerts(&mut self.alerts, conditions, now, self.settings.auto_dismiss_resolved_alerts);
+    }
+    
+    fn render_notifications_window(&mut self, ctx: &Context) {
+        if !self.show_notifications {
+            return;
+        }
+        
+        let now = Local::now();
+        let mut open = true;
+        let mut dismissed = None;
+        let mut raised = None;
+        
+        egui::Window::new("🔔 Notifications")
+            .open(&mut open)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                if self.alerts.is_empty() {
+                    ui.label(RichText::new("No notifications").color(Color32::GRAY));
+                }
+                
+                for (i, alert) in self.alerts.iter().enumerate().rev() {
+                    let color = match alert.kind {
+                        AlertKind::Critical => Color32::from_rgb(231, 76, 60),
+                        AlertKind::Warning => Color32::from_rgb(243, 156, 18),
+                        AlertKind::Info => Color32::from_rgb(52, 152, 219),
+                    };
+                    let opacity = alert.resolved_at.map_or(1.0, |at| {
+                        if self.settings.auto_dismiss_resolved_alerts {
+                            let secs = (now - at).num_milliseconds() as f32 / 1000.0;
+                            (1.0 - secs / RESOLVED_ALERT_FADE_SECS).clamp(0.15, 0.6)
+                        } else {
+                            0.6
+                        }
+                    });
+                    
+                    egui::Frame::none()
+                        .fill(Color32::from_rgb(52, 73, 94).gamma_multiply(opacity))
+                        .rounding(6.0)
+                        .inner_margin(egui::style::Margin::symmetric(8.0, 6.0))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(alert.kind.text()).color(color.gamma_multiply(opacity)).strong());
+                                ui.label(RichText::new(&alert.message).color(Color32::WHITE.gamma_multiply(opacity)));
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    let dismissable = alert.source == AlertSource::Manual || alert.resolved_at.is_some();
+                                    if dismissable && ui.small_button("✖").clicked() {
+                                        dismissed = Some(i);
+                                    }
+                                    let when = match alert.resolved_at {
+                                        Some(at) => format!("✔ resolved {}", self.settings.locale.time(at, false)),
+                                        None => self.settings.locale.time(alert.raised_at, false),
+                                    };
+                                    ui.label(RichText::new(when).color(Color32::GRAY));
+                                });
+                            });
+                        });
+                    ui.add_space(4.0);
+                }
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.manual_alert_draft).hint_text("Raise a manual alert..."));
+                    let text = self.manual_alert_draft.trim();
+                    if ui.add_enabled(!text.is_empty(), egui::Button::new("Raise")).clicked() {
+                        raised = Some(text.to_string());
+                    }
+                });
+            });
+        
+        if self.settings.auto_dismiss_resolved_alerts && self.alerts.iter().any(|a| a.resolved_at.is_some()) {
+            ctx.request_repaint();
+        }
+        if let Some(i) = dismissed {
+            self.alerts.remove(i);
+        }
+        if let Some(message) = raised {
+            self.alerts.push(Alert {
+                kind: AlertKind::Info,
+                source: AlertSource::Manual,
+                message: format!("{}: {}", self.current_user.name, message),
+                raised_at: now,
+                resolved_at: None,
+            });
+            self.manual_alert_draft.clear();
+        }
+        self.show_notifications = open;
+    }
+    
+    /// Reference card built from the same thresholds the status methods classify with.
+    fn render_ranges_legend(&mut self, ctx: &Context) {
+        egui::Window::new("? Vitals Ranges")
+            .open(&mut self.show_ranges_legend)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("ranges_legend")
+                    .num_columns(4)
+                    .spacing([14.0, 6.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Vital");
+                        ui.label(RichText::new("Normal").color(TriageLevel::Low.color()).strong());
+                        ui.label(RichText::new("High").color(TriageLevel::High.color()).strong());
+                        ui.label(RichText::new("Critical").color(TriageLevel::Critical.color()).strong());
+                        ui.end_row();
+                        
+                        for thresholds in ALL_VITAL_THRESHOLDS {
+                            ui.label(format!("{} ({})", thresholds.name, thresholds.unit));
+                            ui.label(thresholds.normal_text());
+                            ui.label(thresholds.high_text());
+                            ui.label(thresholds.critical_text());
+                            ui.end_row();
+                        }
+                    });
+                
+                ui.add_space(8.0);
+                ui.separator();
+                
+                ui.label(RichText::new("Triage colors").strong());
+                for (level, meaning) in TriageLevel::ALL.into_iter().zip([
+                    "Immediate threat to life",
+                    "Urgent, potentially unstable",
+                    "Stable, needs assessment",
+                    "Minor, can wait",
+                ]) {
+                    ui.horizontal(|ui| {
+                        let (dot, _) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
+                        ui.painter().circle_filled(dot.center(), 6.0, level.color());
+                        ui.label(RichText::new(level.text()).color(level.color()).strong());
+                        ui.label(meaning);
+                    });
+                }
+                ui.label(
+                    RichText::new("Vital values are colored Critical / High / Low by the ranges above.")
+                        .color(Color32::GRAY)
+                );
+            });
+    }
+    
+    fn render_settings_window(&mut self, ctx: &Context) {
+        let mut open = self.show_settings;
+        
+        egui::Window::new("⚙ Settings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Signed in as");
+                    ui.text_edit_singleline(&mut self.current_user.name);
+                    egui::ComboBox::from_id_source("current_role")
+                        .selected_text(self.current_user.role.text())
+                        .show_ui(ui, |ui| {
+                            for role in StaffRole::ALL {
+                                ui.selectable_value(&mut self.current_user.role, role, role.text());
+                            }
+                        });
+                });
+                
+                ui.separator();
+                
+                if ui.button("🩺 Data integrity check...").clicked() {
+                    self.run_integrity_check();
+                    self.show_integrity = true;
+                }
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Card actions").strong());
+                    if ui.button(format!("Use {} preset", self.current_user.role.text())).clicked() {
+                        self.settings.card_actions = card_action_preset(self.current_user.role);
+                    }
+                });
+                
+                let mut moved = None;
+                let last = self.settings.card_actions.len().saturating_sub(1);
+                egui::Grid::new("card_actions").num_columns(3).show(ui, |ui| {
+                    for (i, slot) in self.settings.card_actions.iter_mut().enumerate() {
+                        ui.label(slot.action.text());
+                        egui::ComboBox::from_id_source(("card_action_placement", i))
+                            .selected_text(slot.placement.text())
+                            .show_ui(ui, |ui| {
+                                for placement in ActionPlacement::ALL {
+                                    ui.selectable_value(&mut slot.placement, placement, placement.text());
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(i > 0, egui::Button::new("⏶").small()).clicked() {
+                                moved = Some((i, i - 1));
+                            }
+                            if ui.add_enabled(i < last, egui::Button::new("⏷").small()).clicked() {
+                                moved = Some((i, i + 1));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                if let Some((from, to)) = moved {
+                    self.settings.card_actions.swap(from, to);
+                }
+                
+                ui.separator();
+                
+                ui.label(RichText::new("Vitals history retention").strong());
+                
+                let retention = &mut self.settings.vitals_retention;
+                ui.horizontal(|ui| {
+                    let selected = matches!(retention, VitalsRetention::LastSamples(_));
+                    if ui.radio(selected, "Keep last").clicked() && !selected {
+                        *retention = VitalsRetention::LastSamples(360);
+                    }
+                    if let VitalsRetention::LastSamples(max) = retention {
+                        ui.add(egui::DragValue::new(max).clamp_range(1..=10_000).suffix(" samples"));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let selected = matches!(retention, VitalsRetention::LastMinutes(_));
+                    if ui.radio(selected, "Keep last").clicked() && !selected {
+                        *retention = VitalsRetention::LastMinutes(60);
+                    }
+                    if let VitalsRetention::LastMinutes(minutes) = retention {
+                        ui.add(egui::DragValue::new(minutes).clamp_range(1..=1_440).suffix(" min"));
+                    }
+                });
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Mark telemetry stale after");
+                    ui.add(egui::DragValue::new(&mut self.settings.telemetry_stale_secs).clamp_range(10..=3_600).suffix(" s"));
+                });
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Alert sounds").strong());
+                    ui.label(RichText::new("visual cue only (no audio backend)").color(Color32::GRAY).italics());
+                });
+                
+                let audio = &mut self.settings.audio;
+                let mut test = None;
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut audio.muted, "Mute");
+                    egui::ComboBox::from_id_source("sound_pack")
+                        .selected_text(audio.sound_pack.text())
+                        .show_ui(ui, |ui| {
+                            for pack in SoundPack::ALL {
+                                ui.selectable_value(&mut audio.sound_pack, pack, pack.text());
+                            }
+                        });
+                });
+                ui.add_enabled_ui(!audio.muted, |ui| {
+                    egui::Grid::new("alert_volumes").num_columns(3).show(ui, |ui| {
+                        ui.label("Master");
+                        ui.add(egui::Slider::new(&mut audio.master_volume, 0.0..=1.0).show_value(false));
+                        ui.end_row();
+                        
+                        for (kind, volume) in AlertKind::ALL.iter().zip(audio.kind_volume.iter_mut()) {
+                            ui.label(kind.text());
+                            ui.add(egui::Slider::new(volume, 0.0..=1.0).show_value(false));
+                            if ui.small_button("▶ Test").clicked() {
+                                test = Some(*kind);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(kind) = test {
+                    self.play_alert(kind);
+                }
+                if let Some(cue) = &self.audio.last_cue {
+                    ui.label(
+                        RichText::new(format!(
+                            "Last alert: {} ({}) at {:.0}% - {}",
+                            cue.sound,
+                            cue.kind.text(),
+                            cue.volume * 100.0,
+                            self.settings.locale.time(cue.at, true)
+                        ))
+                            .color(Color32::GRAY)
+                    );
+                }
+                
+                ui.separator();
+                
+                ui.label(RichText::new("Language & formatting").strong());
+                
+                let locale = &mut self.settings.locale;
+                ui.horizontal(|ui| {
+                    ui.label("Preset");
+                    let current = LocalePreset::ALL.into_iter().find(|p| p.settings() == LocaleSettings {
+                        timezone_label: LocaleSettings::default().timezone_label,
+                        ..locale.clone()
+                    });
+                    egui::ComboBox::from_id_source("locale_preset")
+                        .selected_text(current.map_or("Custom", |p| p.text()))
+                        .show_ui(ui, |ui| {
+                            for preset in LocalePreset::ALL {
+                                if ui.selectable_label(current == Some(preset), preset.text()).clicked() {
+                                    let timezone_label = std::mem::take(&mut locale.timezone_label);
+                                    *locale = LocaleSettings {
+                                        timezone_label,
+                                        ..preset.settings()
+                                    };
+                                }
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut locale.hour_format, HourFormat::H24, "24-hour");
+                    ui.radio_value(&mut locale.hour_format, HourFormat::H12, "12-hour");
+                    ui.add_space(10.0);
+                    ui.radio_value(&mut locale.date_order, DateOrder::DayMonth, "Day Month");
+                    ui.radio_value(&mut locale.date_order, DateOrder::MonthDay, "Month Day");
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut locale.arabic_indic_digits, "Arabic-Indic digits")
+                        .on_hover_text("Needs a UI font with Arabic glyphs to render");
+                    ui.add_space(10.0);
+                    ui.label("Time zone label");
+                    ui.add(egui::TextEdit::singleline(&mut locale.timezone_label).desired_width(60.0));
+                });
+                
+                ui.separator();
+                
+                ui.label(RichText::new("Routing protocols").strong());
+                
+                let hospital_names: Vec<String> = self.hospitals.iter().map(|h| h.name.clone()).collect();
+                let mut removed = None;
+                let mut rules_changed = false;
+                egui::Grid::new("routing_rules").num_columns(3).show(ui, |ui| {
+                    for (i, rule) in self.settings.routing_rules.iter_mut().enumerate() {
+                        rules_changed |= ui
+                            .add(egui::TextEdit::singleline(&mut rule.keyword).hint_text("Complaint keyword").desired_width(120.0))
+                            .changed();
+                        egui::ComboBox::from_id_source(("routing_target", i))
+                            .selected_text(rule.route_to.text())
+                            .show_ui(ui, |ui| {
+                                for name in &hospital_names {
+                                    rules_changed |= ui.selectable_value(&mut rule.route_to, RouteTo::Hospital(name.clone()), name).changed();
+                                }
+                                ui.separator();
+                                for capability in Capability::ALL {
+                                    let target = RouteTo::Capability(capability);
+                                    let label = target.text();
+                                    rules_changed |= ui.selectable_value(&mut rule.route_to, target, label).changed();
+                                }
+                            });
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = removed {
+                    self.settings.routing_rules.remove(i);
+                    rules_changed = true;
+                }
+                if ui.button("Add protocol").clicked() {
+                    if let Some(name) = hospital_names.first() {
+                        self.settings.routing_rules.push(RoutingRule {
+                            keyword: String::new(),
+                            route_to: RouteTo::Hospital(name.clone()),
+                        });
+                    }
+                }
+                self.sidebar_dirty |= rules_changed;
+                
+                ui.separator();
+                
+                ui.label(RichText::new("Escalation mentions").strong());
+                ui.label(
+                    RichText::new("Automated chat posts @-mention these roles; other events go to the main channel")
+                        .color(Color32::GRAY)
+                );
+                
+                let mut removed = None;
+                egui::Grid::new("escalation_routes").num_columns(3).show(ui, |ui| {
+                    for (i, route) in self.settings.escalation_routes.iter_mut().enumerate() {
+                        egui::ComboBox::from_id_source(("escalation_event", i))
+                            .selected_text(route.event.text())
+                            .show_ui(ui, |ui| {
+                                for event in EscalationEvent::ALL {
+                                    ui.selectable_value(&mut route.event, event, event.text());
+                                }
+                            });
+                        egui::ComboBox::from_id_source(("escalation_role", i))
+                            .selected_text(format!("@{}", route.mention.text()))
+                            .show_ui(ui, |ui| {
+                                for role in StaffRole::ALL {
+                                    ui.selectable_value(&mut route.mention, role, role.text());
+                                }
+                            });
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = removed {
+                    self.settings.escalation_routes.remove(i);
+                }
+                if ui.button("Add mention").clicked() {
+                    self.settings.escalation_routes.push(EscalationRoute {
+                        event: EscalationEvent::NeedsAttention,
+                        mention: StaffRole::ChargeNurse,
+                    });
+                }
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Team presence: online for");
+                    ui.add(egui::DragValue::new(&mut self.settings.presence_online_minutes).clamp_range(1..=120).suffix(" min"));
+                    ui.label("away for");
+                    ui.add(
+                        egui::DragValue::new(&mut self.settings.presence_away_minutes)
+                            .clamp_range(self.settings.presence_online_minutes..=480)
+                            .suffix(" min")
+                    );
+                });
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.idle_dim_enabled, "Dim to summary after");
+                    ui.add_enabled(
+                        self.settings.idle_dim_enabled,
+                        egui::DragValue::new(&mut self.settings.idle_dim_minutes).clamp_range(1..=240).suffix(" min idle")
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.throttle_when_unfocused, "When unfocused, refresh only every");
+                    ui.add_enabled(
+                        self.settings.throttle_when_unfocused,
+                        egui::DragValue::new(&mut self.settings.unfocused_repaint_secs).clamp_range(2..=120).suffix(" s")
+                    );
+                })
+                .response
+                .on_hover_text("New Critical patients still raise an alert and request attention");
+                ui.checkbox(
+                    &mut self.settings.auto_dismiss_resolved_alerts,
+                    "Auto-dismiss notifications once their condition clears"
+                );
+                ui.checkbox(&mut self.settings.reduce_motion, "Reduce motion");
+                ui.checkbox(&mut self.settings.show_frame_timing, "Show sidebar render timing");
+                ui.checkbox(&mut self.settings.show_route_connector, "Draw connector from patient to destination hospital");
+                
+                ui.separator();
+                
+                ui.checkbox(&mut self.settings.auto_triage, "Auto-assign triage from vitals for new patients without one");
+                
+                ui.separator();
+                
+                ui.checkbox(&mut self.settings.reassess_enabled, "Prompt for periodic triage re-assessment");
+                
+                ui.add_enabled_ui(self.settings.reassess_enabled, |ui| {
+                    egui::Grid::new("reassess_windows").num_columns(2).show(ui, |ui| {
+                        for (level, minutes) in TriageLevel::ALL.iter().zip(self.settings.reassess_minutes.iter_mut()) {
+                            ui.label(RichText::new(level.text()).color(level.color()));
+                            ui.add(egui::DragValue::new(minutes).clamp_range(1..=720).suffix(" min"));
+                            ui.end_row();
+                        }
+                    });
+                });
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.auto_archive_enabled, "Archive discharged/transferred patients after");
+                    ui.add_enabled(
+                        self.settings.auto_archive_enabled,
+                        egui::DragValue::new(&mut self.settings.archive_after_minutes).clamp_range(0..=1_440).suffix(" min")
+                    );
+                });
+                
+                ui.separator();
+                
+                ui.checkbox(&mut self.settings.handover_checklist_enabled, "Require handover checklist before accepting");
+                
+                ui.add_enabled_ui(self.settings.handover_checklist_enabled, |ui| {
+                    let mut removed = None;
+                    for (i, item) in self.settings.handover_checklist_items.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(item);
+                            if ui.small_button("✖").clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = removed {
+                        self.settings.handover_checklist_items.remove(i);
+                    }
+                    
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_checklist_item)
+                                .hint_text("New checklist item")
+                        );
+                        if ui.button("Add").clicked() && !self.new_checklist_item.trim().is_empty() {
+                            self.settings.handover_checklist_items.push(self.new_checklist_item.trim().to_string());
+                            self.new_checklist_item.clear();
+                        }
+                    });
+                });
+            });
+        
+        self.show_settings = open;
+    }
+    
+    /// Looks up a patient for an action, recording the current user as the last to touch it.
+    fn touch_patient(&mut self, patient_id: &str) -> Option<&mut Patient> {
+        self.sidebar_dirty = true;
+        let patient = self.patients.iter_mut().find(|p| p.id == patient_id)?;
+        patient.last_touched = Some(LastTouch {
+            by: self.current_user.name.clone(),
+            at: Local::now(),
+        });
+        Some(patient)
+    }
+    
+    /// Assigns an available ambulance unit to the patient and moves it to en route.
+    fn dispatch_ambulance(&mut self, patient_id: &str) {
+        if self.ambulance_available == 0 {
+            return;
+        }
+        if self.patients.iter().any(|p| p.id == patient_id && p.ambulance_id.is_some()) {
+            return;
+        }
+        
+        let unit = next_ambulance_id(self.patients.iter().chain(self.archived_patients.iter()));
+        let Some(patient) = self.touch_patient(patient_id) else {
+            return;
+        };
+        
+        patient.log_event(format!("Ambulance {} dispatched", unit));
+        patient.ambulance_id = Some(unit);
+        self.ambulance_available -= 1;
+        self.ambulance_en_route += 1;
+    }
+    
+    /// Returns the patient's unit to the available pool once they have arrived or been archived.
+    fn release_ambulance(&mut self, patient_id: &str) {
+        let Some(patient) = self
+            .patients
+            .iter_mut()
+            .chain(self.archived_patients.iter_mut())
+            .find(|p| p.id == patient_id)
+        else {
+            return;
+        };
+        let Some(unit) = patient.ambulance_id.clone().filter(|_| !patient.ambulance_released) else {
+            return;
+        };
+        
+        patient.ambulance_released = true;
+        patient.log_event(format!("Ambulance {} released", unit));
+        self.ambulance_en_route = self.ambulance_en_route.saturating_sub(1);
+        self.ambulance_available += 1;
+    }
+    
+    /// Name of the patient's destination if it has no free bed or is oversubscribed.
+    fn over_capacity_destination(&self, patient_id: &str) -> Option<String> {
+        let patient = self.patients.iter().find(|p| p.id == patient_id)?;
+        let hospital = patient.destination(&self.hospitals, &self.settings.routing_rules)?;
+        let full = hospital.available_beds == 0 || hospital.projected_available(&self.patients, &self.hospitals, &self.settings.routing_rules) < 0;
+        full.then(|| hospital.name.clone())
+    }
+    
+    fn begin_accept(&mut self, patient_id: &str) {
+        if let Some(hospital) = self.over_capacity_destination(patient_id) {
+            self.pending_capacity_override = Some(PendingOverride {
+                patient_id: patient_id.to_string(),
+                hospital,
+                assign: false,
+            });
+        } else {
+            self.continue_accept(patient_id);
+        }
+    }
+    
+    /// Assigns a hospital, asking for confirmation first if it is full or on divert.
+    fn begin_assign(&mut self, patient_id: &str, hospital_name: &str) {
+        let Some(hospital) = self.hospitals.iter().find(|h| h.name == hospital_name) else {
+            return;
+        };
+        if hospital.can_accept_now(&self.patients, &self.hospitals, &self.settings.routing_rules) {
+            self.assign_hospital(patient_id, hospital_name);
+        } else {
+            self.pending_capacity_override = Some(PendingOverride {
+                patient_id: patient_id.to_string(),
+                hospital: hospital_name.to_string(),
+                assign: true,
+            });
+        }
+    }
+    
+    fn assign_hospital(&mut self, patient_id: &str, hospital_name: &str) {
+        let Some(eta) = self.hospitals.iter().find(|h| h.name == hospital_name).map(|h| h.distance_minutes) else {
+            return;
+        };
+        
+        if let Some(p) = self.touch_patient(patient_id) {
+            p.assigned_hospital = Some(hospital_name.to_string());
+            if p.eta_minutes.is_some() {
+                p.eta_minutes = Some(eta);
+                p.eta_set_at = Some(Local::now());
+                p.log_event(format!("Assigned to {}, ETA {} min", hospital_name, eta));
+            } else {
+                p.log_event(format!("Assigned to {}", hospital_name));
+            }
+        }
+    }
+    
+    /// Accept flow after any capacity override: the handover checklist, if enabled.
+    fn continue_accept(&mut self, patient_id: &str) {
+        let items = &self.settings.handover_checklist_items;
+        if self.settings.handover_checklist_enabled && !items.is_empty() {
+            self.pending_handover = Some(PendingHandover {
+                patient_id: patient_id.to_string(),
+                checked: vec![false; items.len()],
+            });
+        } else {
+            self.finalize_accept(patient_id, None);
+        }
+    }
+    
+    fn finalize_accept(&mut self, patient_id: &str, checklist: Option<&[(String, bool)]>) {
+        let Some(patient) = self.touch_patient(patient_id) else {
+            return;
+        };
+        
+        patient.accepted = true;
+        patient.accepted_at = Some(Local::now());
+        match checklist {
+            Some(items) => {
+                let summary = items
+                    .iter()
+                    .map(|(item, done)| format!("{} {}", if *done { "✔" } else { "✖" }, item))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                patient.log_event(format!("Accepted after handover checklist: {}", summary));
+            }
+            None => patient.log_event("Accepted"),
+        }
+    }
+    
+    fn render_capacity_override_window(&mut self, ctx: &Context) {
+        let Some(pending) = &self.pending_capacity_override else {
+            return;
+        };
+        let hospital_name = &pending.hospital;
+        let hospital = self.hospitals.iter().find(|h| &h.name == hospital_name);
+        let diverting = hospital.is_some_and(|h| h.on_divert);
+        
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new(format!("Over Capacity - {}", pending.patient_id))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                let warning = if diverting {
+                    format!("⚠ {} is on divert - confirm override?", hospital_name)
+                } else {
+                    format!("⚠ {} is at capacity - confirm override?", hospital_name)
+                };
+                ui.label(
+                    RichText::new(warning)
+                        .color(Color32::from_rgb(243, 156, 18))
+                        .strong()
+                );
+                if let Some(hospital) = hospital {
+                    ui.label(format!(
+                        "{} beds free, {} projected after inbound arrivals.",
+                        hospital.available_beds,
+                        hospital.projected_available(&self.patients, &self.hospitals, &self.settings.routing_rules)
+                    ));
+                }
+                
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    let confirm = if pending.assign { "Assign Anyway" } else { "Accept Over Capacity" };
+                    if ui.button(confirm).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        
+        if confirmed {
+            let Some(PendingOverride { patient_id, hospital: hospital_name, assign }) =
+                self.pending_capacity_override.take()
+            else {
+                return;
+            };
+            let by = self.current_user.name.clone();
+            let kind = if diverting { "Divert" } else { "Over-capacity" };
+            if let Some(patient) = self.touch_patient(&patient_id) {
+                patient.log_event(format!("{} override at {} by {}", kind, hospital_name, by));
+            }
+            if assign {
+                self.assign_hospital(&patient_id, &hospital_name);
+            } else {
+                self.push_error(AppError::OverCapacity {
+                    patient_id: patient_id.clone(),
+                    hospital: hospital_name,
+                });
+                self.continue_accept(&patient_id);
+            }
+        } else if cancelled {
+            self.pending_capacity_override = None;
+        }
+    }
+    
+    fn render_handover_window(&mut self, ctx: &Context) {
+        let Some(pending) = &mut self.pending_handover else {
+            return;
+        };
+        
+        let items = &self.settings.handover_checklist_items;
+        // Items may have been edited in settings while the checklist was open
+        pending.checked.resize(items.len(), false);
+        
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new(format!("Handover Checklist - {}", pending.patient_id))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("Confirm each item before accepting this patient:");
+                ui.add_space(8.0);
+                
+                for (item, checked) in items.iter().zip(pending.checked.iter_mut()) {
+                    ui.checkbox(checked, item);
+                }
+                
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    let complete = pending.checked.iter().all(|c| *c);
+                    if ui.add_enabled(complete, egui::Button::new("Confirm Accept")).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        
+        if confirmed {
+            let checklist: Vec<(String, bool)> = items.iter().cloned().zip(pending.checked.iter().copied()).collect();
+            let patient_id = pending.patient_id.clone();
+            self.pending_handover = None;
+            self.finalize_accept(&patient_id, Some(&checklist));
+        } else if cancelled {
+            self.pending_handover = None;
+        }
+    }
+    
+    fn render_reassessment_window(&mut self, ctx: &Context) {
+        let Some((patient_id, level)) = &mut self.pending_reassessment else {
+            return;
+        };
+        
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new(format!("Re-assess Triage - {}", patient_id))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for option in TriageLevel::ALL {
+                        ui.selectable_value(
+                            level,
+                            option,
+                            RichText::new(option.text()).color(option.color()).strong()
+                        );
+                    }
+                });
+                
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Confirm").clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        
+        if confirmed {
+            let (patient_id, level) = (patient_id.clone(), *level);
+            if let Some(patient) = self.touch_patient(&patient_id) {
+                patient.reassess(level);
+            }
+            self.pending_reassessment = None;
+        } else if cancelled {
+            self.pending_reassessment = None;
+        }
+    }
+    
+    fn render_broadcast_panel(&mut self, ctx: &Context) {
+        let Some(fill) = self.broadcast.as_ref().map(|b| b.severity.color()) else {
+            return;
+        };
+        
+        TopBottomPanel::top("broadcast")
+            .frame(egui::Frame::none().fill(fill))
+            .show(ctx, |ui| {
+                self.render_broadcast_banner(ui);
+            });
+    }
+    
+    fn render_broadcast_banner(&mut self, ui: &mut Ui) {
+        let Some(broadcast) = &self.broadcast else {
+            return;
+        };
+        
+        let mut dismissed = false;
+        
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            ui.add_space(10.0);
+            ui.label(
+                RichText::new(format!("📢 {}: {}", broadcast.severity.text(), broadcast.text))
+                    .font(FontId::new(20.0, FontFamily::Proportional))
+                    .color(Color32::WHITE)
+                    .strong()
+            );
+            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.add_space(10.0);
+                
+                let can_dismiss = self.current_user.role.can_broadcast();
+                let dismiss = ui
+                    .add_enabled(can_dismiss, egui::Button::new(RichText::new("Dismiss").strong()))
+                    .on_disabled_hover_text("Only an ER Director or Charge Nurse can dismiss broadcasts");
+                if dismiss.clicked() {
+                    dismissed = true;
+                }
+                
+                ui.add_space(10.0);
+                
+                ui.label(
+                    RichText::new(format!(
+                        "Issued by {} at {}",
+                        broadcast.issued_by,
+                        self.settings.locale.time(broadcast.issued_at, false)
+                    ))
+                        .font(FontId::new(12.0, FontFamily::Proportional))
+                        .color(Color32::WHITE)
+                );
+            });
+        });
+        ui.add_space(8.0);
+        
+        if dismissed {
+            self.broadcast = None;
+        }
+    }
+    
+    fn render_broadcast_composer(&mut self, ctx: &Context) {
+        let mut open = self.show_broadcast_composer;
+        let mut issued = false;
+        
+        egui::Window::new("📢 Issue Broadcast")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for severity in BroadcastSeverity::ALL {
+                        ui.selectable_value(
+                            &mut self.broadcast_severity,
+                            severity,
+                            RichText::new(severity.text()).color(severity.color()).strong()
+                        );
+                    }
+                });
+                
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.broadcast_input)
+                        .hint_text("e.g. Code Black - lockdown in effect")
+                        .desired_width(320.0)
+                );
+                
+                ui.add_space(8.0);
+                
+                if ui.add_enabled(!self.broadcast_input.trim().is_empty(), egui::Button::new("Broadcast")).clicked() {
+                    issued = true;
+                }
+            });
+        
+        if issued {
+            self.broadcast = Some(Broadcast {
+                text: self.broadcast_input.trim().to_string(),
+                severity: self.broadcast_severity,
+                issued_by: self.current_user.name.clone(),
+                issued_at: Local::now(),
+            });
+            self.play_alert(match self.broadcast_severity {
+                BroadcastSeverity::Critical => AlertKind::Critical,
+                BroadcastSeverity::Warning => AlertKind::Warning,
+                BroadcastSeverity::Info => AlertKind::Info,
+            });
+            self.broadcast_input.clear();
+            open = false;
+        }
+        
+        self.show_broadcast_composer = open;
+    }
+    
+    /// Adds a walk-in patient, resolving triage per `PatientIntake::resolve_triage`.
+    fn admit_intake(&mut self, intake: PatientIntake) -> String {
+        let (triage_level, needs_review) = intake.resolve_triage(self.settings.auto_triage);
+        let now = Local::now();
+        let id = next_patient_id(self.patients.iter().chain(self.archived_patients.iter()));
+        
+        let mut patient = Patient {
+            id: id.clone(),
+            age: intake.age,
+            gender: intake.gender,
+            chief_complaint: intake.chief_complaint,
+            triage_level,
+            // Placeholder until vitals are entered; `awaiting_vitals` keeps it off the board
+            vitals: intake.vitals.clone().unwrap_or_else(|| NewPatientForm::default().draft_vitals),
+            vitals_history: vec![],
+            location: intake.location,
+            zone: None,
+            eta_minutes: None,
+            ambulance_id: None,
+            paramedic: None,
+            assigned_hospital: None,
+            notes: vec![],
+            timestamp: now,
+            last_updated: now,
+            last_triage_at: now,
+            accepted: false,
+            timeline: vec![],
+            code: None,
+            last_touched: Some(LastTouch {
+                by: self.current_user.name.clone(),
+                at: now,
+            }),
+            expected_disposition: None,
+            field_notes: vec![],
+            status: PatientStatus::InTriage,
+            status_changed_at: Some(now),
+            triage_needs_review: needs_review,
+            orders: vec![],
+            triage_history: vec![],
+            awaiting_vitals: intake.vitals.is_none(),
+            eta_set_at: None,
+            in_hospital_location: None,
+            accepted_at: None,
+            ambulance_released: false,
+        };
+        
+        patient.log_event(match (intake.triage, needs_review) {
+            (Some(level), _) => format!("Registered with triage {}", level.text()),
+            (None, false) => format!("Registered; triage {} assigned from vitals", triage_level.text()),
+            (None, true) if intake.vitals.is_none() => "Registered without complete vitals; triage defaulted to MEDIUM for review".to_string(),
+            (None, true) => "Registered without triage; defaulted to MEDIUM for review".to_string(),
+        });
+        
+        self.patients.push(patient);
+        id
+    }
+    
+    fn render_new_patient_window(&mut self, ctx: &Context) {
+        let Some(form) = &mut self.new_patient_form else {
+            return;
+        };
+        
+        let auto_triage = self.settings.auto_triage;
+        let mut open = true;
+        let mut submitted = None;
+        let mut imported = Vec::new();
+        let mut import_error = None;
+        
+        egui::Window::new("➕ New Patient")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let intake = &mut form.intake;
+                
+                egui::Grid::new("new_patient")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Age");
+                        ui.add(egui::DragValue::new(&mut intake.age).clamp_range(0..=120));
+                        ui.end_row();
+                        
+                        ui.label("Gender");
+                        ui.text_edit_singleline(&mut intake.gender);
+                        ui.end_row();
+                        
+                        ui.label("Chief Complaint");
+                        ui.text_edit_singleline(&mut intake.chief_complaint);
+                        ui.end_row();
+                        
+                        ui.label("Location");
+                        ui.text_edit_singleline(&mut intake.location);
+                        ui.end_row();
+                        
+                        ui.label("Triage");
+                        egui::ComboBox::from_id_source("new_patient_triage")
+                            .selected_text(intake.triage.map_or("Auto", |level| level.text()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut intake.triage, None, "Auto");
+                                for level in TriageLevel::ALL {
+                                    ui.selectable_value(&mut intake.triage, Some(level), level.text());
+                                }
+                            });
+                        ui.end_row();
+                    });
+                
+                ui.add_space(6.0);
+                ui.checkbox(&mut form.vitals_taken, "Vitals taken");
+                
+                ui.add_enabled_ui(form.vitals_taken, |ui| {
+                    let draft = &mut form.draft_vitals;
+                    egui::Grid::new("new_patient_vitals")
+                        .num_columns(2)
+                        .spacing([12.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.label("Systolic BP");
+                            int_stepper(ui, &mut draft.blood_pressure.0, &[5], SYSTOLIC_BOUNDS, " mmHg");
+                            ui.end_row();
+                            
+                            ui.label("Diastolic BP");
+                            int_stepper(ui, &mut draft.blood_pressure.1, &[5], DIASTOLIC_BOUNDS, " mmHg");
+                            ui.end_row();
+                            
+                            ui.label("Heart Rate");
+                            int_stepper(ui, &mut draft.heart_rate, &[5], HEART_RATE_BOUNDS, " bpm");
+                            ui.end_row();
+                            
+                            ui.label("O2 Sat");
+                            int_stepper(ui, &mut draft.oxygen_saturation, &[1], OXYGEN_BOUNDS, "%");
+                            ui.end_row();
+                            
+                            ui.label("Temperature");
+                            ui.add(
+                                egui::DragValue::new(&mut draft.temperature)
+                                    .speed(0.1)
+                                    .fixed_decimals(1)
+                                    .clamp_range(TEMPERATURE_BOUNDS)
+                                    .suffix(" °C")
+                            );
+                            ui.end_row();
+                        });
+                });
+                
+                intake.vitals = form.vitals_taken.then(|| form.draft_vitals.clone());
+                
+                let (level, needs_review) = intake.resolve_triage(auto_triage);
+                let preview = if intake.triage.is_some() {
+                    RichText::new(format!("Triage: {}", level.text())).color(level.color())
+                } else if needs_review {
+                    RichText::new("Triage will default to MEDIUM and be flagged for review")
+                        .color(Color32::from_rgb(243, 156, 18))
+                } else {
+                    RichText::new(format!("Auto triage from vitals: {}", level.text())).color(level.color())
+                };
+                ui.add_space(6.0);
+                ui.label(preview.strong());
+                
+                let warnings = intake.vitals.as_ref().map(vitals_sanity_check).unwrap_or_default();
+                for warning in &warnings {
+                    ui.label(RichText::new(format!("⚠ {}", warning)).color(Color32::from_rgb(231, 76, 60)));
+                }
+                
+                ui.add_space(6.0);
+                let ready = !intake.chief_complaint.trim().is_empty() && warnings.is_empty();
+                if ui.add_enabled(ready, egui::Button::new("Add Patient")).clicked() {
+                    submitted = Some(intake.clone());
+                }
+                
+                ui.separator();
+                
+                egui::CollapsingHeader::new("Import CSV").show(ui, |ui| {
+                    ui.label(
+                        RichText::new("age,gender,complaint,location,triage,systolic,diastolic,hr,o2,temp")
+                            .monospace()
+                            .color(Color32::GRAY)
+                    );
+                    ui.label(
+                        RichText::new("Leave triage blank or \"auto\" to derive it from vitals.")
+                            .color(Color32::GRAY)
+                    );
+                    ui.add(
+                        egui::TextEdit::multiline(&mut form.import_text)
+                            .desired_rows(4)
+                            .code_editor()
+                    );
+                    if ui.button("Import").clicked() {
+                        form.import_report.clear();
+                        let mut total = 0;
+                        for (n, line) in form.import_text.lines().enumerate() {
+                            if line.trim().is_empty() {
+                                continue;
+                            }
+                            total += 1;
+                            match PatientIntake::from_csv_line(line) {
+                                Ok(intake) => imported.push(intake),
+                                Err(error) => form.import_report.push(format!("Line {}: {}", n + 1, error)),
+                            }
+                        }
+                        let failed = form.import_report.len();
+                        if failed > 0 {
+                            import_error = Some(AppError::Import { failed, total });
+                        }
+                    }
+                    for line in &form.import_report {
+                        ui.label(RichText::new(line).color(Color32::LIGHT_GRAY));
+                    }
+                });
+            });
+        
+        if let Some(error) = import_error {
+            self.push_error(error);
+        }
+        
+        if !imported.is_empty() {
+            let count = imported.len();
+            for intake in imported {
+                self.admit_intake(intake);
+            }
+            if let Some(form) = &mut self.new_patient_form {
+                form.import_report.insert(0, format!("Imported {} patient(s)", count));
+                form.import_text.clear();
+            }
+            if self.settings.integrity_check_on_import {
+                self.run_integrity_check();
+                self.show_integrity |= self.integrity_report.as_ref().is_some_and(|(_, issues)| !issues.is_empty());
+            }
+        }
+        
+        if let Some(intake) = submitted {
+            let id = self.admit_intake(intake);
+            self.select_patient(&id);
+            self.new_patient_form = None;
+        } else if !open {
+            self.new_patient_form = None;
+        }
+    }
+    
+    fn render_vitals_editor(&mut self, ctx: &Context) {
+        let Some(editor) = &mut self.vitals_editor else {
+            return;
+        };
+        
+        let mut saved = false;
+        let mut cancelled = false;
+        
+        egui::Window::new(format!("Edit Vitals - {}", editor.patient_id))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let draft = &mut editor.draft;
+                
+                egui::Grid::new("vitals_editor")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Systolic BP");
+                        int_stepper(ui, &mut draft.blood_pressure.0, &[1, 5], SYSTOLIC_BOUNDS, " mmHg");
+                        ui.end_row();
+                        
+                        ui.label("Diastolic BP");
+                        int_stepper(ui, &mut draft.blood_pressure.1, &[1, 5], DIASTOLIC_BOUNDS, " mmHg");
+                        ui.end_row();
+                        
+                        ui.label("Heart Rate");
+                        int_stepper(ui, &mut draft.heart_rate, &[1, 5], HEART_RATE_BOUNDS, " bpm");
+                        ui.end_row();
+                        
+                        ui.label("O2 Sat");
+                        int_stepper(ui, &mut draft.oxygen_saturation, &[1], OXYGEN_BOUNDS, "%");
+                        ui.end_row();
+                        
+                        ui.label("Temperature");
+                        ui.horizontal(|ui| {
+                            if repeat_button(ui, "−0.1") {
+                                draft.temperature -= 0.1;
+                            }
+                            ui.add(
+                                egui::DragValue::new(&mut draft.temperature)
+                                    .speed(0.1)
+                                    .fixed_decimals(1)
+                                    .suffix(" °C")
+                            );
+                            if repeat_button(ui, "+0.1") {
+                                draft.temperature += 0.1;
+                            }
+                        });
+                        ui.end_row();
+                    });
+                
+                let mut errors = validate_vitals(draft);
+                errors.extend(vitals_sanity_check(draft).iter().map(Warning::to_string));
+                
+                ui.add_space(6.0);
+                for error in &errors {
+                    ui.label(RichText::new(format!("⚠ {}", error)).color(Color32::from_rgb(231, 76, 60)));
+                }
+                
+                ui.add_space(6.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(errors.is_empty(), egui::Button::new("Save")).clicked() {
+                        saved = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        
+        if saved {
+            let Some(editor) = self.vitals_editor.take() else {
+                return;
+            };
+            let retention = self.settings.vitals_retention;
+            if let Some(patient) = self.touch_patient(&editor.patient_id) {
+                let now = Local::now();
+                let v = &editor.draft;
+                patient.log_event(format!(
+                    "Vitals corrected: BP {}/{}, HR {}, O2 {}%, Temp {:.1}°C",
+                    v.blood_pressure.0, v.blood_pressure.1, v.heart_rate, v.oxygen_saturation, v.temperature
+                ));
+                patient.vitals = editor.draft;
+                patient.awaiting_vitals = false;
+                patient.last_updated = now;
+                patient.record_vitals(retention, now);
+            }
+        } else if cancelled {
+            self.vitals_editor = None;
+        }
+    }
+    
+    fn render_patient_details(&mut self, ctx: &Context) {
+        let Some(patient) = self.selected_patient().cloned() else {
+            return;
+        };
+        
+        let mut open = self.show_patient_details;
+        let mut disposition = patient.expected_disposition;
+        let mut status = patient.status;
+        let mut saved_location = None;
+        let mut new_order = None;
+        let was_pinned = self.pinned_patients.contains(&patient.id);
+        let mut pinned = was_pinned;
+        let mut completed_order = None;
+        
+        if self.location_draft.as_ref().is_none_or(|(id, _)| *id != patient.id) {
+            let draft = patient.in_hospital_location.clone().unwrap_or_default();
+            self.location_draft = Some((patient.id.clone(), draft));
+        }
+        let draft = self.location_draft.as_mut().map(|(_, draft)| draft);
+        
+        egui::Window::new(format!("Patient Details - {}", patient.id))
+            .id(egui::Id::new("patient_details"))
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{}{}  •  {}  •  {}",
+                        patient.age,
+                        patient.gender,
+                        patient.chief_complaint,
+                        patient.triage_level.text()
+                    ))
+                        .color(patient.triage_level.color())
+                        .strong()
+                );
+                
+                ui.checkbox(&mut pinned, "📌 Pinned to top of board");
+                
+                ui.add_space(8.0);
+                
+                ui.horizontal(|ui| {
+                    ui.label("Status:");
+                    egui::ComboBox::from_id_source(format!("status_{}", patient.id))
+                        .selected_text(status.text())
+                        .show_ui(ui, |ui| {
+                            for option in PatientStatus::ALL {
+                                ui.selectable_value(&mut status, option, option.text());
+                            }
+                        });
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label("Expected disposition:");
+                    egui::ComboBox::from_id_source(format!("disposition_{}", patient.id))
+                        .selected_text(disposition.map_or("Not set", |d| d.text()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut disposition, None, "Not set");
+                            for option in Disposition::ALL {
+                                ui.selectable_value(&mut disposition, Some(option), option.text());
+                            }
+                        });
+                });
+                
+                if let Some(draft) = draft.filter(|_| status.is_in_hospital()) {
+                    ui.add_space(8.0);
+                    ui.label(RichText::new("In-hospital location").strong());
+                    egui::Grid::new("in_hospital_location").num_columns(2).show(ui, |ui| {
+                        ui.label("Building");
+                        ui.text_edit_singleline(&mut draft.building);
+                        ui.end_row();
+                        
+                        ui.label("Floor");
+                        ui.text_edit_singleline(&mut draft.floor);
+                        ui.end_row();
+                        
+                        ui.label("Room / Bay");
+                        ui.text_edit_singleline(&mut draft.bay);
+                        ui.end_row();
+                    });
+                    
+                    let changed = patient.in_hospital_location.as_ref() != Some(&*draft);
+                    let complete = !draft.building.trim().is_empty() && !draft.bay.trim().is_empty();
+                    if ui.add_enabled(changed && complete, egui::Button::new("Save Location")).clicked() {
+                        saved_location = Some(draft.clone());
+                    }
+                }
+                
+                ui.add_space(8.0);
+                ui.separator();
+                
+                let pending = patient.orders.iter().filter(|o| o.status == OrderStatus::Pending).count();
+                ui.label(RichText::new(format!("Orders ({} pending)", pending)).strong());
+                for (i, order) in patient.orders.iter().enumerate().filter(|(_, o)| o.status == OrderStatus::Pending) {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✔").on_hover_text("Mark completed").clicked() {
+                            completed_order = Some(i);
+                        }
+                        ui.label(&order.text);
+                        ui.label(
+                            RichText::new(format!("{} • {}", order.ordered_by, self.settings.locale.time(order.at, false)))
+                                .color(Color32::GRAY)
+                        );
+                    });
+                }
+                for order in patient.orders.iter().filter(|o| o.status == OrderStatus::Completed) {
+                    ui.label(RichText::new(format!("✔ {}", order.text)).color(Color32::GRAY).strikethrough());
+                }
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.order_draft).hint_text("Medication, lab, imaging...")
+                    );
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let text = self.order_draft.trim();
+                    if (ui.add_enabled(!text.is_empty(), egui::Button::new("Add Order")).clicked() || submitted)
+                        && !text.is_empty()
+                    {
+                        new_order = Some(text.to_string());
+                    }
+                });
+                
+                ui.add_space(8.0);
+                ui.separator();
+                
+                ui.label(RichText::new("Timeline").strong());
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    if patient.timeline.is_empty() {
+                        ui.label(RichText::new("No events yet").color(Color32::GRAY));
+                    }
+                    for event in patient.timeline.iter().rev() {
+                        ui.label(format!("{}  {}", self.settings.locale.time(event.at, true), event.text));
+                    }
+                });
+            });
+        
+        self.show_patient_details = open;
+        
+        if status != patient.status {
+            if let Some(p) = self.touch_patient(&patient.id) {
+                p.set_status(status);
+            }
+            if status != PatientStatus::EnRoute {
+                self.release_ambulance(&patient.id);
+            }
+        }
+        
+        if pinned != was_pinned {
+            self.toggle_pin(&patient.id);
+        }
+        
+        if let Some(text) = new_order {
+            let by = self.current_user.name.clone();
+            if let Some(p) = self.touch_patient(&patient.id) {
+                p.add_order(&text, &by);
+            }
+            self.order_draft.clear();
+        }
+        
+        if let Some(index) = completed_order {
+            let by = self.current_user.name.clone();
+            if let Some(p) = self.touch_patient(&patient.id) {
+                p.complete_order(index, &by);
+            }
+        }
+        
+        if let Some(location) = saved_location {
+            if let Some(p) = self.touch_patient(&patient.id) {
+                p.log_event(format!("Moved to {}", location));
+                p.in_hospital_location = Some(location);
+            }
+        }
+        
+        if disposition != patient.expected_disposition {
+            if let Some(p) = self.touch_patient(&patient.id) {
+                p.log_event(format!(
+                    "Expected disposition set to {}",
+                    disposition.map_or("not set", |d| d.text())
+                ));
+                p.expected_disposition = disposition;
+            }
+        }
+    }
+    
+    fn push_error(&mut self, error: AppError) {
+        self.errors.push((Local::now(), error));
+        if self.errors.len() > MAX_ERROR_BANNERS {
+            if let Some(oldest) = self.errors.iter().position(|(_, e)| e.expires_after_secs().is_some()) {
+                self.errors.remove(oldest);
+            }
+        }
+    }
+    
+    fn render_error_banners(&mut self, ui: &mut Ui) {
+        let now = Local::now();
+        self.errors.retain(|(at, error)| {
+            error.expires_after_secs().is_none_or(|secs| (now - *at).num_seconds() < secs)
+        });
+        
+        let mut dismissed = None;
+        for (i, (at, error)) in self.errors.iter().enumerate() {
+            let banner = egui::Frame::none()
+                .fill(Color32::from_rgb(231, 76, 60))
+                .rounding(6.0)
+                .inner_margin(egui::style::Margin::symmetric(10.0, 6.0));
+            
+            banner.show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("⚠ {}", error))
+                            .font(FontId::new(12.0, FontFamily::Proportional))
+                            .color(Color32::WHITE)
+                            .strong()
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").clicked() {
+                            dismissed = Some(i);
+                        }
+                        ui.label(
+                            RichText::new(self.settings.locale.time(*at, true))
+                                .font(FontId::new(11.0, FontFamily::Proportional))
+                                .color(Color32::from_gray(235))
+                        );
+                    });
+                });
+            });
+            ui.add_space(4.0);
+        }
+        
+        if let Some(i) = dismissed {
+            self.errors.remove(i);
+        }
+        if !self.errors.is_empty() {
+            ui.add_space(6.0);
+        }
+    }
+    
+    fn render_header(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add_space(10.0);
+            
+            // Logo and title
+            ui.label(
+                RichText::new("🏥 Dubai Health Authority - Emergency Response")
+                    .font(FontId::new(18.0, FontFamily::Proportional))
                     .color(Color32::WHITE)
                     .strong()
             );
             
-            ui.add_space(20.0);
+            ui.add_space(20.0);
+            
+            // Emergency status
+            let emergency_count = self.patients.len();
+            ui.label(
+                RichText::new(format!("🚨 {} ACTIVE EMERGENCIES", emergency_count))
+                    .font(FontId::new(14.0, FontFamily::Proportional))
+                    .color(Color32::from_rgb(231, 76, 60))
+                    .strong()
+            );
+            
+            // Incident clock
+            if let Some(incident) = self.incident.as_ref().filter(|i| i.is_active()) {
+                ui.add_space(20.0);
+                
+                let clock_frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(231, 76, 60))
+                    .rounding(6.0)
+                    .inner_margin(egui::style::Margin::symmetric(10.0, 4.0));
+                
+                clock_frame.show(ui, |ui| {
+                    ui.label(
+                        RichText::new(self.settings.locale.digits(&format!("⏱ MCI {}", format_hh_mm_ss(incident.elapsed_secs(Local::now())))))
+                            .font(FontId::new(18.0, FontFamily::Monospace))
+                            .color(Color32::WHITE)
+                            .strong()
+                    );
+                })
+                    .response
+                    .on_hover_text(format!(
+                        "Declared by {} at {}",
+                        incident.declared_by,
+                        self.settings.locale.time(incident.declared_at, true)
+                    ));
+            }
+            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("⚙").on_hover_text("Settings").clicked() {
+                    self.show_settings = !self.show_settings;
+                }
+                
+                ui.toggle_value(&mut self.show_ranges_legend, "?")
+                    .on_hover_text("Vitals ranges and triage colors");
+                
+                let active_alerts = self.alerts.iter().filter(|a| a.resolved_at.is_none()).count();
+                let bell = RichText::new(format!("🔔 {}", self.settings.locale.digits(&active_alerts.to_string())));
+                let bell = if active_alerts > 0 { bell.color(Color32::from_rgb(243, 156, 18)) } else { bell };
+                ui.toggle_value(&mut self.show_notifications, bell)
+                    .on_hover_text("Notifications");
+                
+                if ui.button("➕ New Patient").clicked() && self.new_patient_form.is_none() {
+                    self.new_patient_form = Some(NewPatientForm::default());
+                }
+                
+                let can_broadcast = self.current_user.role.can_broadcast();
+                if ui
+                    .add_enabled(can_broadcast, egui::Button::new("📢 Issue Broadcast"))
+                    .on_disabled_hover_text("Only an ER Director or Charge Nurse can issue broadcasts")
+                    .clicked()
+                {
+                    self.show_broadcast_composer = true;
+                }
+                
+                ui.add_space(10.0);
+                
+                // Current time
+                let now = Local::now();
+                ui.label(
+                    RichText::new(format!("🕐 {} {}", self.settings.locale.time(now, true), self.settings.locale.timezone_label))
+                        .color(Color32::LIGHT_GRAY)
+                );
+                
+                ui.add_space(15.0);
+                
+                // User info
+                ui.label(
+                    RichText::new(format!("👨‍⚕️ {} - {}", self.current_user.name, self.current_user.role.text()))
+                        .font(FontId::new(12.0, FontFamily::Proportional))
+                        .color(Color32::from_rgb(46, 204, 113))
+                );
+                
+                ui.add_space(15.0);
+                
+                // Location
+                ui.label(
+                    RichText::new("📍 Dubai Healthcare City")
+                        .color(Color32::LIGHT_GRAY)
+                );
+            });
+        });
+        
+        ui.add_space(5.0);
+        ui.separator();
+    }
+    
+    fn render_sidebar(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        
+        // Hospitals section
+        ui.label(
+            RichText::new("🏥 DHA HOSPITALS")
+                .font(FontId::new(14.0, FontFamily::Proportional))
+                .color(Color32::LIGHT_GRAY)
+                .strong()
+        );
+        
+        ui.add_space(10.0);
+        
+        let sidebar_started = std::time::Instant::now();
+        if self.sidebar_dirty || self.sidebar_cache.fonts_changed(ui) {
+            self.sidebar_cache.rebuild(ui, &self.hospitals, &self.specialists);
+            self.hospital_rects.clear();
+            self.sidebar_dirty = false;
+        }
+        
+        let focused = self.focused_patient();
+        let cached = self
+            .sidebar_cache
+            .focus_destination
+            .as_ref()
+            .filter(|(id, _)| id.as_deref() == focused.map(|p| p.id.as_str()))
+            .map(|(_, index)| *index);
+        let focus_destination = match cached {
+            Some(index) => index,
+            None => {
+                let index = focused
+                    .and_then(|p| p.destination(&self.hospitals, &self.settings.routing_rules))
+                    .and_then(|h| self.hospitals.iter().position(|other| other.name == h.name));
+                self.sidebar_cache.focus_destination = Some((focused.map(|p| p.id.clone()), index));
+                index
+            }
+        };
+        
+        // Pulse the highlight unless motion is reduced
+        let time = ui.input(|i| i.time);
+        let glow_alpha = if self.settings.reduce_motion {
+            255
+        } else {
+            (155.0 + 100.0 * (time * 4.0).sin()) as u8
+        };
+        if focus_destination.is_some() && !self.settings.reduce_motion {
+            ui.ctx().request_repaint();
+        }
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, row) in self.sidebar_cache.hospitals.iter().enumerate() {
+                let is_selected = i == 0; // Dubai Hospital selected by default
+                let is_destination = focus_destination == Some(i);
+                
+                let bg_color = if is_selected {
+                    Color32::from_rgb(63, 81, 181)
+                } else {
+                    Color32::from_rgb(52, 73, 94)
+                };
+                
+                let stroke = if is_destination {
+                    Stroke::new(3.0, Color32::from_rgba_unmultiplied(52, 152, 219, glow_alpha))
+                } else {
+                    Stroke::NONE
+                };
+                
+                let frame = egui::Frame::none()
+                    .fill(bg_color)
+                    .stroke(stroke)
+                    .rounding(6.0)
+                    .inner_margin(egui::style::Margin::same(8.0));
+                
+                let hospital_frame = frame.show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(row.name_text.clone());
+                            
+                            ui.horizontal(|ui| {
+                                // Bed status indicator
+                                ui.painter().circle_filled(
+                                    ui.next_widget_position() + Vec2::new(4.0, 4.0),
+                                    4.0,
+                                    row.bed_color,
+                                );
+                                ui.add_space(12.0);
+                                
+                                ui.label(row.bed_text.clone());
+                                
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.label(row.distance_text.clone());
+                                });
+                            });
+                        });
+                    });
+                });
+                
+                // Only the first frame after a rebuild allocates a key
+                let rect = hospital_frame.response.rect;
+                match self.hospital_rects.get_mut(&row.name) {
+                    Some(existing) => *existing = rect,
+                    None => {
+                        self.hospital_rects.insert(row.name.clone(), rect);
+                    }
+                }
+                
+                ui.add_space(8.0);
+            }
+            
+            ui.add_space(15.0);
+            
+            // Specialists section
+            ui.label(
+                RichText::new("👨‍⚕️ SPECIALISTS ON-CALL")
+                    .font(FontId::new(14.0, FontFamily::Proportional))
+                    .color(Color32::LIGHT_GRAY)
+                    .strong()
+            );
+            
+            ui.add_space(10.0);
+            
+            for row in &self.sidebar_cache.specialists {
+                let frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(61, 86, 117))
+                    .rounding(6.0)
+                    .inner_margin(egui::style::Margin::same(8.0));
+                
+                frame.show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(row.label.clone());
+                        
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.painter().circle_filled(
+                                ui.next_widget_position() + Vec2::new(5.0, 5.0),
+                                5.0,
+                                row.status_color,
+                            );
+                            ui.add_space(15.0);
+                        });
+                    });
+                });
+                
+                ui.add_space(5.0);
+            }
+            
+            ui.add_space(15.0);
+            
+            // Ambulance status section
+            ui.label(
+                RichText::new("🚑 AMBULANCE STATUS")
+                    .font(FontId::new(14.0, FontFamily::Proportional))
+                    .color(Color32::LIGHT_GRAY)
+                    .strong()
+            );
+            
+            ui.add_space(10.0);
+            
+            let frame = egui::Frame::none()
+                .fill(Color32::from_rgb(52, 73, 94))
+                .rounding(6.0)
+                .inner_margin(egui::style::Margin::same(10.0));
+            
+            frame.show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(
+                            RichText::new(format!("{}", self.ambulance_available))
+                                .font(FontId::new(18.0, FontFamily::Proportional))
+                                .color(Color32::from_rgb(46, 204, 113))
+                                .strong()
+                        );
+                        ui.label(
+                            RichText::new("Available")
+                                .font(FontId::new(10.0, FontFamily::Proportional))
+                                .color(Color32::LIGHT_GRAY)
+                        );
+                    });
+                    
+                    ui.add_space(20.0);
+                    
+                    ui.vertical(|ui| {
+                        ui.label(
+                            RichText::new(format!("{}", self.ambulance_en_route))
+                                .font(FontId::new(18.0, FontFamily::Proportional))
+                                .color(Color32::from_rgb(231, 76, 60))
+                                .strong()
+                        );
+                        ui.label(
+                            RichText::new("En Route")
+                                .font(FontId::new(10.0, FontFamily::Proportional))
+                                .color(Color32::LIGHT_GRAY)
+                        );
+                    });
+                    
+                    ui.add_space(20.0);
+                    
+                    ui.vertical(|ui| {
+                        ui.label(
+                            RichText::new(format!("{}", self.ambulance_at_scene))
+                                .font(FontId::new(18.0, FontFamily::Proportional))
+                                .color(Color32::from_rgb(243, 156, 18))
+                                .strong()
+                        );
+                        ui.label(
+                            RichText::new("At Scene")
+                                .font(FontId::new(10.0, FontFamily::Proportional))
+                                .color(Color32::LIGHT_GRAY)
+                        );
+                    });
+                });
+            });
+            
+            self.sidebar_timing.record(sidebar_started.elapsed());
+            if self.settings.show_frame_timing {
+                ui.add_space(10.0);
+                ui.label(
+                    RichText::new(format!(
+                        "Sidebar {:.3} ms avg / {:.3} ms max over {} frames • {} cache rebuilds",
+                        self.sidebar_timing.average_ms(),
+                        self.sidebar_timing.max_ms(),
+                        self.sidebar_timing.len(),
+                        self.sidebar_cache.rebuilds
+                    ))
+                        .font(FontId::new(10.0, FontFamily::Monospace))
+                        .color(Color32::GRAY)
+                );
+            }
+        });
+    }
+    
+    /// Stacked bar of the board's acuity mix; clicking a segment filters the board to that level.
+    fn render_triage_strip(&mut self, ui: &mut Ui) {
+        let counts = TriageLevel::ALL.map(|level| self.patients.iter().filter(|p| p.triage_level == level).count());
+        let total: usize = counts.iter().sum();
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 20.0), egui::Sense::hover());
+        
+        if total == 0 {
+            ui.painter().rect_filled(rect, 4.0, Color32::from_rgb(52, 73, 94));
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "No active patients",
+                FontId::new(11.0, FontFamily::Proportional),
+                Color32::GRAY,
+            );
+            return;
+        }
+        
+        let mut left = rect.left();
+        for (level, count) in TriageLevel::ALL.into_iter().zip(counts) {
+            if count == 0 {
+                continue;
+            }
+            
+            let width = rect.width() * count as f32 / total as f32;
+            let segment = egui::Rect::from_min_size(egui::pos2(left, rect.top()), Vec2::new(width, rect.height()));
+            left += width;
+            
+            let response = ui
+                .interact(segment, ui.id().with(level.text()), egui::Sense::click())
+                .on_hover_text(format!("{} {} - click to filter the board", self.settings.locale.digits(&count.to_string()), level.text()));
+            let active = self.patient_filter.triage == Some(level);
+            let dimmed = self.patient_filter.triage.is_some() && !active;
+            let fill = if dimmed { level.color().gamma_multiply(0.35) } else { level.color() };
+            
+            ui.painter().rect_filled(segment.shrink2(Vec2::new(1.0, 0.0)), 4.0, fill);
+            if active || response.hovered() {
+                ui.painter().rect_stroke(segment.shrink(1.0), 4.0, egui::Stroke::new(2.0, Color32::WHITE));
+            }
+            ui.painter().text(
+                segment.center(),
+                egui::Align2::CENTER_CENTER,
+                self.settings.locale.digits(&count.to_string()),
+                FontId::new(12.0, FontFamily::Proportional),
+                Color32::WHITE,
+            );
+            
+            if response.clicked() {
+                self.patient_filter.triage = if active { None } else { Some(level) };
+                self.active_tab = 0;
+                self.show_archive = false;
+            }
+        }
+    }
+    
+    fn render_main_content(&mut self, ui: &mut Ui) {
+        self.hovered_patient = None;
+        self.patient_card_rects.clear();
+        
+        self.render_error_banners(ui);
+        
+        // Tabs
+        ui.horizontal(|ui| {
+            let tabs = ["🚨 Active Emergencies", "📋 Incoming Patients", "🏥 Hospital Status", "📊 Analytics", "🗺 Incident Command"];
+            
+            for (i, tab) in tabs.iter().enumerate() {
+                let is_active = i == self.active_tab;
+                
+                if ui.selectable_label(is_active, *tab).clicked() {
+                    self.active_tab = i;
+                }
+                
+                ui.add_space(10.0);
+            }
+        });
+        
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(15.0);
+        
+        // Content based on active tab
+        match self.active_tab {
+            0 => self.render_active_emergencies(ui),
+            1 => self.render_incoming_patients(ui),
+            2 => self.render_hospital_status(ui),
+            3 => self.render_analytics(ui),
+            4 => self.render_incident_command(ui),
+            _ => {}
+        }
+    }
+    
+    fn render_active_emergencies(&mut self, ui: &mut Ui) {
+        let now = Local::now();
+        let due_count = self
+            .patients
+            .iter()
+            .filter(|p| p.reassess_due(now, &self.settings))
+            .count();
+        let attention_count = self
+            .patients
+            .iter()
+            .filter(|p| p.needs_attention(now, &self.settings))
+            .count();
+        
+        if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(egui::Key::U)) {
+            self.jump_to_most_urgent();
+        }
+        
+        // A selection hidden by the filters would never scroll into view, so reveal it
+        if self.scroll_to_selected {
+            if let Some(patient) = self.selected_patient() {
+                let pinned = self.pinned_patients.contains(&patient.id);
+                if !pinned && !self.patient_filter.matches(patient, now, &self.settings) {
+                    self.selection_notice = Some(format!("Filters cleared to show {}", patient.id));
+                    self.patient_filter = PatientFilter::default();
+                }
+            }
+        }
+        
+        // Filter bar
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.board_view, BoardView::Cards, "▦ Cards");
+            ui.selectable_value(&mut self.board_view, BoardView::Table, "☰ Table");
+            
+            egui::ComboBox::from_id_source("group_by")
+                .selected_text(self.settings.group_by.text())
+                .show_ui(ui, |ui| {
+                    for group_by in GroupBy::ALL {
+                        ui.selectable_value(&mut self.settings.group_by, group_by, group_by.text());
+                    }
+                });
+            
+            ui.add_space(10.0);
+            
+            ui.toggle_value(&mut self.show_archive, format!("🗄 Archive ({})", self.archived_patients.len()));
+            
+            ui.add_space(10.0);
+            
+            ui.checkbox(
+                &mut self.patient_filter.reassess_due_only,
+                format!("⏰ Re-assess due only ({})", due_count)
+            );
+            ui.checkbox(
+                &mut self.patient_filter.needs_attention_only,
+                format!("⚠ Needs attention only ({})", attention_count)
+            );
+            if let Some(level) = self.patient_filter.triage {
+                if ui
+                    .button(RichText::new(format!("{} only ✖", level.text())).color(level.color()))
+                    .on_hover_text("Clear triage filter")
+                    .clicked()
+                {
+                    self.patient_filter.triage = None;
+                }
+            }
+            
+            ui.add_space(10.0);
+            
+            if ui
+                .button("⚡ Most urgent")
+                .on_hover_text(format!(
+                    "Select the most urgent patient not yet accepted (U); repeat to cycle through the top {}",
+                    URGENT_CYCLE_LEN
+                ))
+                .clicked()
+            {
+                self.jump_to_most_urgent();
+            }
+            ui.checkbox(&mut self.urgent_ignores_filters, "ignore filters");
+            
+            if let Some(notice) = &self.selection_notice {
+                ui.add_space(10.0);
+                ui.label(RichText::new(notice).color(Color32::from_rgb(243, 156, 18)));
+                if ui.small_button("✖").clicked() {
+                    self.selection_notice = None;
+                }
+            }
+        });
+        
+        ui.add_space(10.0);
+        
+        if self.show_archive {
+            self.render_archive(ui);
+            return;
+        }
+        
+        // Clone patients to avoid borrow checker issues
+        self.pinned_patients.retain(|id| self.patients.iter().any(|p| &p.id == id));
+        let pinned: Vec<Patient> = self
+            .pinned_patients
+            .iter()
+            .filter_map(|id| self.patients.iter().find(|p| &p.id == id))
+            .cloned()
+            .collect();
+        let patients: Vec<Patient> = self
+            .patients
+            .iter()
+            .filter(|p| !self.pinned_patients.contains(&p.id))
+            .filter(|p| self.patient_filter.matches(p, now, &self.settings))
+            .cloned()
+            .collect();
+        
+        // Arrow keys move the selection through the visible cards, pinned ones first
+        let visible: Vec<&str> = pinned.iter().chain(patients.iter()).map(|p| p.id.as_str()).collect();
+        if !ui.ctx().wants_keyboard_input() && !visible.is_empty() {
+            let (up, down) = ui.input(|i| (i.key_pressed(egui::Key::ArrowUp), i.key_pressed(egui::Key::ArrowDown)));
+            if up || down {
+                let current = visible
+                    .iter()
+                    .position(|id| self.selected_patient.as_deref() == Some(*id));
+                let next = match (current, down) {
+                    (None, true) => 0,
+                    (None, false) => visible.len() - 1,
+                    (Some(i), true) => (i + 1).min(visible.len() - 1),
+                    (Some(i), false) => i.saturating_sub(1),
+                };
+                self.select_patient(visible[next]);
+            }
+        }
+        
+        let group_by = self.settings.group_by;
+        let groups = group_by.group(patients, &self.hospitals, &self.settings.routing_rules);
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if !pinned.is_empty() {
+                ui.label(
+                    RichText::new(format!("📌 PINNED ({})", pinned.len()))
+                        .font(FontId::new(14.0, FontFamily::Proportional))
+                        .color(Color32::LIGHT_GRAY)
+                        .strong()
+                );
+                ui.add_space(6.0);
+                self.render_patient_list(ui, &pinned);
+                ui.separator();
+                ui.add_space(8.0);
+            }
             
-            // Emergency status
-            let emergency_count = self.patients.len();
-            ui.label(
-                RichText::new(format!("🚨 {} ACTIVE EMERGENCIES", emergency_count))
+            for (label, members) in &groups {
+                if group_by == GroupBy::None {
+                    self.render_patient_list(ui, members);
+                    continue;
+                }
+                
+                let critical = members.iter().filter(|p| p.triage_level == TriageLevel::Critical).count();
+                let waiting = members.iter().filter(|p| !p.accepted).count();
+                let header = RichText::new(format!(
+                    "{}  ({})  •  {} critical  •  {} awaiting acceptance",
+                    label,
+                    members.len(),
+                    critical,
+                    waiting
+                ))
                     .font(FontId::new(14.0, FontFamily::Proportional))
-                    .color(Color32::from_rgb(231, 76, 60))
-                    .strong()
-            );
+                    .strong();
+                
+                // Expand the group holding a selection that needs to scroll into view
+                let reveal = self.scroll_to_selected
+                    && members.iter().any(|p| self.selected_patient.as_deref() == Some(p.id.as_str()));
+                
+                egui::CollapsingHeader::new(header)
+                    .id_source(("patient_group", group_by.text(), label))
+                    .default_open(true)
+                    .open(reveal.then_some(true))
+                    .show(ui, |ui| {
+                        self.render_patient_list(ui, members);
+                    });
+                ui.add_space(8.0);
+            }
+        });
+    }
+    
+    /// Renders one run of patients in the current board view.
+    fn render_patient_list(&mut self, ui: &mut Ui, patients: &[Patient]) {
+        if self.board_view == BoardView::Table {
+            // Key the table on its first patient so grouped tables keep separate state
+            ui.push_id(patients.first().map_or("", |p| p.id.as_str()), |ui| {
+                self.render_patient_table(ui, patients);
+            });
+            return;
+        }
+        
+        ui.vertical(|ui| {
+            for patient in patients {
+                // Scope every widget id in the card to the patient, not its position
+                ui.push_id(&patient.id, |ui| {
+                    self.render_patient_card(ui, patient);
+                });
+                ui.add_space(15.0); // Add spacing between cards
+            }
+        });
+    }
+    
+    fn reroute_patient(&mut self, patient_id: &str, hospital_name: &str) {
+        let Some(hospital) = self.hospitals.iter().find(|h| h.name == hospital_name) else {
+            return;
+        };
+        let (name, eta) = (hospital.name.clone(), hospital.distance_minutes);
+        
+        if let Some(p) = self.touch_patient(patient_id) {
+            let from = p.assigned_hospital.replace(name.clone()).unwrap_or_default();
+            p.eta_minutes = Some(eta);
+            p.eta_set_at = Some(Local::now());
+            p.log_event(format!("Re-routed from {} to {} (destination unavailable), ETA {} min", from, name, eta));
+        }
+    }
+    
+    fn toggle_pin(&mut self, patient_id: &str) {
+        if let Some(index) = self.pinned_patients.iter().position(|id| id == patient_id) {
+            self.pinned_patients.remove(index);
+        } else {
+            self.pinned_patients.push(patient_id.to_string());
+        }
+    }
+    
+    /// Selects the highest-priority unaccepted patient, or the next of the top
+    /// `URGENT_CYCLE_LEN` if one of them is already selected.
+    fn jump_to_most_urgent(&mut self) {
+        let now = Local::now();
+        let mut candidates: Vec<&Patient> = self
+            .patients
+            .iter()
+            .filter(|p| !p.accepted)
+            .filter(|p| self.urgent_ignores_filters || self.patient_filter.matches(p, now, &self.settings))
+            .collect();
+        candidates.sort_by_key(|p| std::cmp::Reverse(p.priority_score(now)));
+        candidates.truncate(URGENT_CYCLE_LEN);
+        
+        if candidates.is_empty() {
+            self.selection_notice = Some("No unaccepted patients to jump to".to_string());
+            return;
+        }
+        
+        let next = candidates
+            .iter()
+            .position(|p| self.selected_patient.as_deref() == Some(p.id.as_str()))
+            .map_or(0, |i| (i + 1) % candidates.len());
+        let patient_id = candidates[next].id.clone();
+        self.select_patient(&patient_id);
+    }
+    
+    fn render_patient_table(&mut self, ui: &mut Ui, patients: &[Patient]) {
+        use egui_extras::{Column, TableBuilder};
+        
+        let now = Local::now();
+        let mut clicked = None;
+        
+        TableBuilder::new(ui)
+            .vscroll(false)
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::auto().at_least(80.0))
+            .column(Column::remainder().at_least(140.0))
+            .column(Column::auto().at_least(90.0))
+            .column(Column::auto().at_least(70.0))
+            .column(Column::auto().at_least(80.0))
+            .column(Column::auto().at_least(120.0))
+            .header(22.0, |mut header| {
+                for title in ["ID", "Triage", "Chief Complaint", "Status", "Vitals", "ETA", "Destination"] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|mut body| {
+                for patient in patients {
+                    let is_selected = self.selected_patient.as_deref() == Some(patient.id.as_str());
+                    
+                    body.row(26.0, |mut row| {
+                        row.col(|ui| {
+                            if ui.selectable_label(is_selected, &patient.id).clicked() {
+                                clicked = Some(patient.id.clone());
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(RichText::new(patient.triage_level.text()).color(patient.triage_level.color()).strong());
+                        });
+                        row.col(|ui| {
+                            ui.label(&patient.chief_complaint);
+                        });
+                        row.col(|ui| {
+                            ui.label(patient.status.text());
+                        });
+                        row.col(|ui| match patient.recorded_vitals() {
+                            Some(vitals) => paint_vitals_dots(ui, vitals),
+                            None => {
+                                ui.label(RichText::new("not recorded").color(Color32::GRAY));
+                            }
+                        });
+                        row.col(|ui| {
+                            match patient.eta_countdown(now, self.settings.telemetry_stale_secs) {
+                                Some(countdown) if countdown.stale => {
+                                    ui.label(RichText::new("stale").color(Color32::GRAY));
+                                }
+                                Some(countdown) => {
+                                    ui.label(self.settings.locale.digits(&format_mm_ss(countdown.remaining_secs)));
+                                }
+                                None => {
+                                    ui.label("-");
+                                }
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(patient.destination(&self.hospitals, &self.settings.routing_rules).map_or("-", |h| h.name.as_str()));
+                        });
+                    });
+                }
+            });
+        
+        if let Some(patient_id) = clicked {
+            self.select_patient(&patient_id);
+        }
+    }
+    
+    fn render_archive(&mut self, ui: &mut Ui) {
+        if self.archived_patients.is_empty() {
+            ui.label(RichText::new("No archived patients").color(Color32::GRAY));
+            return;
+        }
+        
+        let mut restored = None;
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for patient in self.archived_patients.iter().rev() {
+                let frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(52, 73, 94))
+                    .stroke(Stroke::new(1.0, patient.triage_level.color()))
+                    .rounding(8.0)
+                    .inner_margin(egui::style::Margin::same(10.0));
+                
+                frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(format!(
+                                "{}  •  {}  •  {}  •  {}",
+                                patient.id,
+                                patient.chief_complaint,
+                                patient.triage_level.text(),
+                                patient.status.text()
+                            ))
+                                .font(FontId::new(13.0, FontFamily::Proportional))
+                                .color(Color32::WHITE)
+                        );
+                        
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("Restore").on_hover_text("Return to the active board, e.g. if discharged in error").clicked() {
+                                restored = Some(patient.id.clone());
+                            }
+                        });
+                    });
+                    
+                    egui::CollapsingHeader::new(format!("Timeline ({})", patient.timeline.len()))
+                        .id_source(format!("archived_timeline_{}", patient.id))
+                        .show(ui, |ui| {
+                            for event in patient.timeline.iter().rev() {
+                                ui.label(
+                                    RichText::new(format!("{}  {}", self.settings.locale.time(event.at, true), event.text))
+                                        .font(FontId::new(11.0, FontFamily::Proportional))
+                                        .color(Color32::LIGHT_GRAY)
+                                );
+                            }
+                        });
+                });
+                
+                ui.add_space(8.0);
+            }
+        });
+        
+        if let Some(patient_id) = restored {
+            self.restore_archived(&patient_id);
+        }
+    }
+    
+    fn render_patient_card(&mut self, ui: &mut Ui, patient: &Patient) {
+        let triage_color = patient.triage_level.color();
+        let is_selected = self.selected_patient.as_deref() == Some(patient.id.as_str());
+        
+        let frame = egui::Frame::none()
+            .fill(Color32::from_gray(245))
+            .stroke(Stroke::new(if is_selected { 5.0 } else { 3.0 }, triage_color))
+            .rounding(12.0)
+            .inner_margin(egui::style::Margin::same(15.0));
+        
+        let card = frame.show(ui, |ui| {
+            ui.set_width(ui.available_width()); // Use full available width
+            
+            // Patient header
+            ui.horizontal(|ui| {
+                let id_label = ui.add(
+                    egui::Label::new(
+                        RichText::new(&patient.id)
+                            .font(FontId::new(16.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(50))
+                            .strong()
+                    )
+                    .sense(egui::Sense::click())
+                );
+                if id_label.on_hover_text("Click to select").clicked() {
+                    if is_selected {
+                        self.selected_patient = None;
+                    } else {
+                        self.select_patient(&patient.id);
+                    }
+                }
+                
+                let is_pinned = self.pinned_patients.contains(&patient.id);
+                let pin = ui
+                    .add(egui::SelectableLabel::new(is_pinned, "📌"))
+                    .on_hover_text(if is_pinned { "Unpin" } else { "Pin to top" });
+                if pin.clicked() {
+                    self.toggle_pin(&patient.id);
+                }
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let triage_frame = egui::Frame::none()
+                        .fill(triage_color)
+                        .rounding(20.0)
+                        .inner_margin(egui::style::Margin::symmetric(12.0, 6.0));
+                    
+                    triage_frame.show(ui, |ui| {
+                        ui.label(
+                            RichText::new(patient.triage_level.text())
+                                .font(FontId::new(12.0, FontFamily::Proportional))
+                                .color(Color32::WHITE)
+                                .strong()
+                        );
+                    });
+                    
+                    if patient.triage_needs_review {
+                        let review_frame = egui::Frame::none()
+                            .fill(Color32::from_rgb(243, 156, 18))
+                            .rounding(20.0)
+                            .inner_margin(egui::style::Margin::symmetric(10.0, 6.0));
+                        
+                        review_frame.show(ui, |ui| {
+                            ui.label(
+                                RichText::new("⚠ TRIAGE REVIEW")
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::WHITE)
+                                    .strong()
+                            );
+                        })
+                            .response
+                            .on_hover_text("Triage was defaulted at intake; use Re-assess to confirm it");
+                    }
+                    
+                    if patient.reassess_due(Local::now(), &self.settings) {
+                        let due_frame = egui::Frame::none()
+                            .fill(Color32::from_rgb(142, 68, 173))
+                            .rounding(20.0)
+                            .inner_margin(egui::style::Margin::symmetric(10.0, 6.0));
+                        
+                        due_frame.show(ui, |ui| {
+                            ui.label(
+                                RichText::new("⏰ RE-ASSESS DUE")
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::WHITE)
+                                    .strong()
+                            );
+                        });
+                    }
+                    
+                    if let Some(disposition) = patient.expected_disposition {
+                        let disposition_frame = egui::Frame::none()
+                            .stroke(Stroke::new(1.5, disposition.color()))
+                            .rounding(20.0)
+                            .inner_margin(egui::style::Margin::symmetric(10.0, 5.0));
+                        
+                        disposition_frame.show(ui, |ui| {
+                            ui.label(
+                                RichText::new(format!("→ {}", disposition.text()))
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(disposition.color())
+                                    .strong()
+                            );
+                        });
+                    }
+                });
+            });
+            
+            ui.add_space(10.0);
+            
+            // Patient details - now stacked vertically
+            ui.vertical(|ui| {
+                // Status
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("Status:")
+                            .font(FontId::new(13.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(100))
+                            .strong()
+                    );
+                    ui.label(
+                        RichText::new(patient.status.text())
+                            .font(FontId::new(13.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(50))
+                    );
+                });
+                
+                ui.add_space(5.0);
+                
+                // Age/Gender
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("Age/Gender:")
+                            .font(FontId::new(13.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(100))
+                            .strong()
+                    );
+                    ui.label(
+                        RichText::new(format!("{}{}", patient.age, patient.gender))
+                            .font(FontId::new(13.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(50))
+                    );
+                });
+                
+                ui.add_space(5.0);
+                
+                // Chief Complaint
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("Chief Complaint:")
+                            .font(FontId::new(13.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(100))
+                            .strong()
+                    );
+                    ui.label(
+                        RichText::new(&patient.chief_complaint)
+                            .font(FontId::new(13.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(50))
+                    );
+                });
+                
+                ui.add_space(5.0);
+                
+                // Ambulance (if exists)
+                if let Some(ambulance) = &patient.ambulance_id {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new("Ambulance:")
+                                .font(FontId::new(13.0, FontFamily::Proportional))
+                                .color(Color32::from_gray(100))
+                                .strong()
+                        );
+                        ui.label(
+                            RichText::new(ambulance)
+                                .font(FontId::new(13.0, FontFamily::Proportional))
+                                .color(Color32::from_gray(50))
+                        );
+                    });
+                    ui.add_space(5.0);
+                }
+                
+                // Paramedic (if exists)
+                if let Some(paramedic) = &patient.paramedic {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new("Paramedic:")
+                                .font(FontId::new(13.0, FontFamily::Proportional))
+                                .color(Color32::from_gray(100))
+                                .strong()
+                        );
+                        ui.label(
+                            RichText::new(paramedic)
+                                .font(FontId::new(13.0, FontFamily::Proportional))
+                                .color(Color32::from_gray(50))
+                        );
+                    });
+                    ui.add_space(5.0);
+                }
+            });
+            
+            ui.add_space(8.0);
+            
+            // Location
+            let location_frame = egui::Frame::none()
+                .fill(Color32::from_rgb(220, 240, 255))
+                .stroke(Stroke::new(1.0, Color32::from_rgb(52, 152, 219)))
+                .rounding(6.0)
+                .inner_margin(egui::style::Margin::same(8.0));
+            
+            let (location_icon, location_text) = patient.current_location();
+            location_frame.show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(location_icon);
+                    ui.label(
+                        RichText::new(location_text)
+                            .font(FontId::new(12.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(50))
+                    );
+                });
+            });
             
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                // Current time
-                let now = Local::now();
-                ui.label(
-                    RichText::new(format!("🕐 {} GST", now.format("%H:%M:%S")))
-                        .color(Color32::LIGHT_GRAY)
-                );
+            ui.add_space(8.0);
+            
+            // Vitals display
+            let vitals_frame = egui::Frame::none()
+                .fill(Color32::from_gray(236))
+                .rounding(8.0)
+                .inner_margin(egui::style::Margin::same(12.0));
+            
+            vitals_frame.show(ui, |ui| {
+                if patient.awaiting_vitals {
+                    ui.label(
+                        RichText::new("Vitals not recorded")
+                            .font(FontId::new(14.0, FontFamily::Proportional))
+                            .color(Color32::from_gray(100))
+                            .italics()
+                    );
+                    return;
+                }
                 
-                ui.add_space(15.0);
+                egui::Grid::new(("vitals", &patient.id))
+                    .num_columns(3)
+                    .spacing([10.0, 0.0])
+                    .show(ui, |ui| {
+                        // Blood pressure
+                        ui.vertical_centered(|ui| {
+                            ui.label(
+                                RichText::new(format!("{}/{}", patient.vitals.blood_pressure.0, patient.vitals.blood_pressure.1))
+                                    .font(FontId::new(18.0, FontFamily::Proportional))
+                                    .color(patient.vitals.bp_status().color())
+                                    .strong()
+                            );
+                            ui.label(
+                                RichText::new("BP")
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::from_gray(100))
+                            );
+                        });
+                        
+                        // Heart rate
+                        ui.vertical_centered(|ui| {
+                            ui.label(
+                                RichText::new(format!("{}", patient.vitals.heart_rate))
+                                    .font(FontId::new(18.0, FontFamily::Proportional))
+                                    .color(patient.vitals.hr_status().color())
+                                    .strong()
+                            );
+                            ui.label(
+                                RichText::new("HR")
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::from_gray(100))
+                            );
+                        });
+                        
+                        // Oxygen saturation
+                        ui.vertical_centered(|ui| {
+                            ui.label(
+                                RichText::new(format!("{}%", patient.vitals.oxygen_saturation))
+                                    .font(FontId::new(18.0, FontFamily::Proportional))
+                                    .color(patient.vitals.o2_status().color())
+                                    .strong()
+                            );
+                            ui.label(
+                                RichText::new("O2 Sat")
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::from_gray(100))
+                            );
+                        });
+                    });
                 
-                // User info
+                let trend = patient.vitals_trend();
+                ui.add_space(4.0);
                 ui.label(
-                    RichText::new("👨‍⚕️ Dr. Ahmed Al-Mansoori - ER Director")
+                    RichText::new(trend.text())
                         .font(FontId::new(12.0, FontFamily::Proportional))
-                        .color(Color32::from_rgb(46, 204, 113))
-                );
+                        .color(trend.color())
+                        .strong()
+                )
+                    .on_hover_text(format!("NEWS2 (partial) now {}", patient.vitals.news2()));
                 
-                ui.add_space(15.0);
+                let warnings = vitals_sanity_check(&patient.vitals);
+                if !warnings.is_empty() {
+                    ui.add_space(4.0);
+                    let hover = warnings.iter().map(Warning::to_string).collect::<Vec<_>>().join("\n");
+                    ui.label(
+                        RichText::new("⚠ Implausible vitals - check source")
+                            .font(FontId::new(11.0, FontFamily::Proportional))
+                            .color(Color32::from_rgb(243, 156, 18))
+                    )
+                        .on_hover_text(hover);
+                }
                 
-                // Location
+                if patient.vitals_history.len() >= 2 {
+                    ui.add_space(6.0);
+                    paint_heart_rate_history(ui, &patient.vitals_history, &self.settings.locale);
+                }
+            });
+            
+            if let Some(note) = routing_note(patient, &self.hospitals, &self.settings.routing_rules) {
                 ui.label(
-                    RichText::new("📍 Dubai Healthcare City")
-                        .color(Color32::LIGHT_GRAY)
+                    RichText::new(format!("↪ {}", note))
+                        .font(FontId::new(11.0, FontFamily::Proportional))
+                        .color(Color32::from_rgb(243, 156, 18))
                 );
-            });
-        });
-        
-        ui.add_space(5.0);
-        ui.separator();
-    }
-    
-    fn render_sidebar(&mut self, ui: &mut Ui) {
-        ui.add_space(10.0);
-        
-        // Hospitals section
-        ui.label(
-            RichText::new("🏥 DHA HOSPITALS")
-                .font(FontId::new(14.0, FontFamily::Proportional))
-                .color(Color32::LIGHT_GRAY)
-                .strong()
-        );
-        
-        ui.add_space(10.0);
-        
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for (i, hospital) in self.hospitals.iter().enumerate() {
-                let is_selected = i == 0; // Dubai Hospital selected by default
-                
-                let bg_color = if is_selected {
-                    Color32::from_rgb(63, 81, 181)
-                } else {
-                    Color32::from_rgb(52, 73, 94)
-                };
-                
-                let frame = egui::Frame::none()
-                    .fill(bg_color)
+            }
+            
+            if unavailable_destination(patient, &self.hospitals).is_some() {
+                let suggested = reroute_suggestion(patient, &self.hospitals, &self.settings.routing_rules)
+                    .map(|h| (h.name.clone(), h.distance_minutes));
+                ui.add_space(6.0);
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(231, 76, 60))
                     .rounding(6.0)
-                    .inner_margin(egui::style::Margin::same(8.0));
-                
-                frame.show(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.vertical(|ui| {
+                    .inner_margin(egui::style::Margin::symmetric(10.0, 6.0))
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            let warning = if suggested.is_some() {
+                                "⚠ Destination unavailable — re-route?"
+                            } else {
+                                "⚠ Destination unavailable — no alternative hospital can accept"
+                            };
                             ui.label(
-                                RichText::new(&hospital.name)
-                                    .font(FontId::new(13.0, FontFamily::Proportional))
+                                RichText::new(warning)
+                                    .font(FontId::new(12.0, FontFamily::Proportional))
                                     .color(Color32::WHITE)
                                     .strong()
                             );
-                            
-                            ui.horizontal(|ui| {
-                                // Bed status indicator
-                                let bed_color = if hospital.available_beds > 2 {
-                                    Color32::from_rgb(46, 204, 113)
-                                } else if hospital.available_beds > 0 {
-                                    Color32::from_rgb(243, 156, 18)
-                                } else {
-                                    Color32::from_rgb(231, 76, 60)
-                                };
-                                
-                                ui.painter().circle_filled(
-                                    ui.next_widget_position() + Vec2::new(4.0, 4.0),
-                                    4.0,
-                                    bed_color,
-                                );
-                                ui.add_space(12.0);
-                                
-                                let bed_text = if hospital.available_beds > 0 {
-                                    format!("{} Available", hospital.available_beds)
-                                } else {
-                                    "Full Capacity".to_string()
-                                };
-                                
-                                ui.label(
-                                    RichText::new(bed_text)
-                                        .font(FontId::new(11.0, FontFamily::Proportional))
-                                        .color(Color32::LIGHT_GRAY)
-                                );
-                                
-                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                    ui.label(
-                                        RichText::new(format!("{} min", hospital.distance_minutes))
-                                            .font(FontId::new(11.0, FontFamily::Proportional))
-                                            .color(Color32::LIGHT_GRAY)
-                                    );
-                                });
-                            });
+                            if let Some((name, eta)) = &suggested {
+                                if ui.button(format!("Re-route to {} ({} min)", name, eta)).clicked() {
+                                    self.reroute_patient(&patient.id, name);
+                                }
+                            }
                         });
                     });
-                });
-                
-                ui.add_space(8.0);
             }
             
-            ui.add_space(15.0);
-            
-            // Specialists section
-            ui.label(
-                RichText::new("👨‍⚕️ SPECIALISTS ON-CALL")
-                    .font(FontId::new(14.0, FontFamily::Proportional))
-                    .color(Color32::LIGHT_GRAY)
-                    .strong()
-            );
-            
-            ui.add_space(10.0);
+            ui.add_space(8.0);
             
-            for specialist in &self.specialists {
-                let frame = egui::Frame::none()
-                    .fill(Color32::from_rgb(61, 86, 117))
+            // ETA display
+            let now = Local::now();
+            let destination = patient
+                .destination(&self.hospitals, &self.settings.routing_rules)
+                .map_or("unassigned", |h| h.name.as_str());
+            if let Some(countdown) = patient.eta_countdown(now, self.settings.telemetry_stale_secs) {
+                let (fill, text_color) = if countdown.stale {
+                    (Color32::from_gray(170), Color32::from_gray(70))
+                } else {
+                    (Color32::from_rgb(52, 152, 219), Color32::WHITE)
+                };
+                
+                let eta_frame = egui::Frame::none()
+                    .fill(fill)
                     .rounding(6.0)
                     .inner_margin(egui::style::Margin::same(8.0));
                 
-                frame.show(ui, |ui| {
-                    ui.horizontal(|ui| {
+                let eta_text = if countdown.stale {
+                    format!(
+                        "⚠ STALE - ETA ~{} min → {} (no update for {} min)",
+                        (countdown.remaining_secs + 59) / 60,
+                        destination,
+                        (now - patient.last_updated).num_minutes()
+                    )
+                } else if countdown.remaining_secs == 0 {
+                    format!("Arriving now → {}", destination)
+                } else {
+                    format!(
+                        "ETA: {}:{:02} → {}",
+                        countdown.remaining_secs / 60,
+                        countdown.remaining_secs % 60,
+                        destination
+                    )
+                };
+                
+                eta_frame.show(ui, |ui| {
+                    ui.centered_and_justified(|ui| {
                         ui.label(
-                            RichText::new(format!("{} - {}", specialist.name, specialist.specialty))
+                            RichText::new(self.settings.locale.digits(&eta_text))
                                 .font(FontId::new(12.0, FontFamily::Proportional))
-                                .color(Color32::WHITE)
+                                .color(text_color)
+                                .strong()
                         );
-                        
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            let status_color = if specialist.available {
-                                Color32::from_rgb(46, 204, 113)
-                            } else if specialist.on_call {
-                                Color32::from_rgb(243, 156, 18)
-                            } else {
-                                Color32::from_rgb(231, 76, 60)
-                            };
-                            
-                            ui.painter().circle_filled(
-                                ui.next_widget_position() + Vec2::new(5.0, 5.0),
-                                5.0,
-                                status_color,
-                            );
-                            ui.add_space(15.0);
-                        });
                     });
                 });
+            } else {
+                let status_frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(52, 152, 219))
+                    .rounding(6.0)
+                    .inner_margin(egui::style::Margin::same(8.0));
                 
-                ui.add_space(5.0);
-            }
-            
-            ui.add_space(15.0);
-            
-            // Ambulance status section
-            ui.label(
-                RichText::new("🚑 AMBULANCE STATUS")
-                    .font(FontId::new(14.0, FontFamily::Proportional))
-                    .color(Color32::LIGHT_GRAY)
-                    .strong()
-            );
-            
-            ui.add_space(10.0);
-            
-            let frame = egui::Frame::none()
-                .fill(Color32::from_rgb(52, 73, 94))
-                .rounding(6.0)
-                .inner_margin(egui::style::Margin::same(10.0));
-            
-            frame.show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    ui.vertical(|ui| {
-                        ui.label(
-                            RichText::new(format!("{}", self.ambulance_available))
-                                .font(FontId::new(18.0, FontFamily::Proportional))
-                                .color(Color32::from_rgb(46, 204, 113))
-                                .strong()
-                        );
-                        ui.label(
-                            RichText::new("Available")
-                                .font(FontId::new(10.0, FontFamily::Proportional))
-                                .color(Color32::LIGHT_GRAY)
-                        );
-                    });
-                    
-                    ui.add_space(20.0);
-                    
-                    ui.vertical(|ui| {
-                        ui.label(
-                            RichText::new(format!("{}", self.ambulance_en_route))
-                                .font(FontId::new(18.0, FontFamily::Proportional))
-                                .color(Color32::from_rgb(231, 76, 60))
-                                .strong()
-                        );
-                        ui.label(
-                            RichText::new("En Route")
-                                .font(FontId::new(10.0, FontFamily::Proportional))
-                                .color(Color32::LIGHT_GRAY)
-                        );
-                    });
-                    
-                    ui.add_space(20.0);
-                    
-                    ui.vertical(|ui| {
+                status_frame.show(ui, |ui| {
+                    ui.centered_and_justified(|ui| {
                         ui.label(
-                            RichText::new(format!("{}", self.ambulance_at_scene))
-                                .font(FontId::new(18.0, FontFamily::Proportional))
-                                .color(Color32::from_rgb(243, 156, 18))
+                            RichText::new(patient.status_line())
+                                .font(FontId::new(12.0, FontFamily::Proportional))
+                                .color(Color32::WHITE)
                                 .strong()
                         );
-                        ui.label(
-                            RichText::new("At Scene")
-                                .font(FontId::new(10.0, FontFamily::Proportional))
-                                .color(Color32::LIGHT_GRAY)
-                        );
                     });
                 });
-            });
-        });
-    }
-    
-    fn render_main_content(&mut self, ui: &mut Ui) {
-        // Tabs
-        ui.horizontal(|ui| {
-            let tabs = vec!["🚨 Active Emergencies", "📋 Incoming Patients", "🏥 Hospital Status", "📊 Analytics"];
+            }
             
-            for (i, tab) in tabs.iter().enumerate() {
-                let is_active = i == self.active_tab;
+            ui.add_space(10.0);
+            
+            if let Some(code) = &patient.code {
+                self.render_code_panel(ui, patient, code);
+                ui.add_space(10.0);
+            }
+            
+            if patient.eta_minutes.is_some() {
+                self.render_field_notes(ui, patient);
+                ui.add_space(10.0);
+            }
+            
+            // Action buttons
+            let slots = self.settings.card_actions.clone();
+            ui.horizontal_wrapped(|ui| {
+                for slot in slots.iter().filter(|slot| slot.placement == ActionPlacement::Primary) {
+                    if self.render_card_action(ui, patient, slot.action) {
+                        ui.add_space(8.0);
+                    }
+                }
                 
-                if ui.selectable_label(is_active, *tab).clicked() {
-                    self.active_tab = i;
+                if slots.iter().any(|slot| slot.placement == ActionPlacement::Overflow) {
+                    ui.menu_button(
+                        RichText::new("⋯")
+                            .font(FontId::new(12.0, FontFamily::Proportional))
+                            .color(Color32::WHITE),
+                        |ui| {
+                            for slot in slots.iter().filter(|slot| slot.placement == ActionPlacement::Overflow) {
+                                self.render_card_action(ui, patient, slot.action);
+                            }
+                        }
+                    );
                 }
+            });
+            
+            if let Some(touch) = &patient.last_touched {
+                ui.add_space(6.0);
+                ui.label(
+                    RichText::new(format!("✋ Last touched by {} at {}", touch.by, self.settings.locale.time(touch.at, false)))
+                        .font(FontId::new(10.0, FontFamily::Proportional))
+                        .color(Color32::from_gray(120))
+                        .italics()
+                );
+            }
+            
+            if !patient.timeline.is_empty() {
+                ui.add_space(8.0);
                 
-                ui.add_space(10.0);
+                egui::CollapsingHeader::new(
+                    RichText::new(format!("Timeline ({})", patient.timeline.len()))
+                        .font(FontId::new(12.0, FontFamily::Proportional))
+                        .color(Color32::from_gray(80))
+                )
+                    .id_source(format!("timeline_{}", patient.id))
+                    .show(ui, |ui| {
+                        for event in patient.timeline.iter().rev() {
+                            ui.label(
+                                RichText::new(format!("{}  {}", self.settings.locale.time(event.at, true), event.text))
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::from_gray(60))
+                            );
+                        }
+                    });
             }
-        });
+        }).response;
         
-        ui.add_space(10.0);
-        ui.separator();
-        ui.add_space(15.0);
+        if card.hovered() {
+            self.hovered_patient = Some(patient.id.clone());
+        }
+        if is_selected && self.scroll_to_selected {
+            card.scroll_to_me(None);
+            self.scroll_to_selected = false;
+        }
+        self.patient_card_rects.insert(patient.id.clone(), card.rect);
+    }
+    
+    /// Draws one configured card action. Returns false when the action doesn't apply to this
+    /// patient and nothing was drawn.
+    fn render_card_action(&mut self, ui: &mut Ui, patient: &Patient, action: CardAction) -> bool {
+        let label = |text: &str| {
+            RichText::new(text)
+                .font(FontId::new(12.0, FontFamily::Proportional))
+                .color(Color32::WHITE)
+        };
         
-        // Content based on active tab
-        match self.active_tab {
-            0 => self.render_active_emergencies(ui),
-            1 => self.render_incoming_patients(ui),
-            2 => self.render_hospital_status(ui),
-            3 => self.render_analytics(ui),
-            _ => {}
+        let clicked = match action {
+            CardAction::Accept => {
+                let text = if patient.accepted { "✔ Accepted" } else { "Accept" };
+                let clicked = ui.add_enabled(!patient.accepted, egui::Button::new(label(text))).clicked();
+                if clicked {
+                    self.begin_accept(&patient.id);
+                }
+                clicked
+            }
+            CardAction::Details => {
+                let clicked = ui.button(label(action.text())).clicked();
+                if clicked {
+                    self.select_patient(&patient.id);
+                    self.show_patient_details = true;
+                }
+                clicked
+            }
+            CardAction::CallSpecialist => {
+                // Handle specialist call
+                ui.button(label(action.text())).clicked()
+            }
+            CardAction::AddNotes => {
+                // Handle notes
+                ui.button(label(action.text())).clicked()
+            }
+            CardAction::Reassess => {
+                let clicked = ui.button(label(action.text())).clicked();
+                if clicked {
+                    self.pending_reassessment = Some((patient.id.clone(), patient.triage_level));
+                }
+                clicked
+            }
+            CardAction::EditVitals => {
+                let clicked = ui.button(label(action.text())).clicked();
+                if clicked {
+                    self.vitals_editor = Some(VitalsEditor {
+                        patient_id: patient.id.clone(),
+                        draft: patient.vitals.clone(),
+                    });
+                }
+                clicked
+            }
+            CardAction::DispatchAmbulance => {
+                if patient.ambulance_id.is_some() {
+                    return false;
+                }
+                let clicked = ui
+                    .add_enabled(self.ambulance_available > 0, egui::Button::new(label(action.text())))
+                    .on_disabled_hover_text("No ambulances available")
+                    .clicked();
+                if clicked {
+                    self.dispatch_ambulance(&patient.id);
+                }
+                clicked
+            }
+            CardAction::AssignHospital => {
+                let mut assigned = None;
+                ui.menu_button(label(action.text()), |ui| {
+                    for hospital in &self.hospitals {
+                        let current = patient.assigned_hospital.as_ref() == Some(&hospital.name);
+                        if ui.selectable_label(current, &hospital.name).clicked() {
+                            assigned = Some(hospital.name.clone());
+                            ui.close_menu();
+                        }
+                    }
+                });
+                if let Some(name) = assigned.filter(|name| patient.assigned_hospital.as_ref() != Some(name)) {
+                    self.begin_assign(&patient.id, &name);
+                }
+                false
+            }
+            CardAction::StartCode => {
+                if patient.code.is_some() {
+                    return false;
+                }
+                let clicked = ui.button(
+                    RichText::new(action.text())
+                        .font(FontId::new(12.0, FontFamily::Proportional))
+                        .color(Color32::from_rgb(231, 76, 60))
+                        .strong()
+                ).clicked();
+                if clicked {
+                    if let Some(p) = self.touch_patient(&patient.id) {
+                        p.start_code();
+                    }
+                    self.post_escalation(EscalationEvent::CodeCalled, format!("Code called for {}", patient.id));
+                }
+                clicked
+            }
+        };
+        
+        // Choosing an action from the overflow menu closes it
+        if clicked {
+            ui.close_menu();
         }
+        true
     }
     
-    fn render_active_emergencies(&mut self, ui: &mut Ui) {
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            // Clone patients to avoid borrow checker issues
-            let patients = self.patients.clone();
+    fn render_field_notes(&mut self, ui: &mut Ui, patient: &Patient) {
+        let notes_frame = egui::Frame::none()
+            .fill(Color32::from_rgb(255, 248, 225))
+            .stroke(Stroke::new(1.0, Color32::from_rgb(243, 156, 18)))
+            .rounding(6.0)
+            .inner_margin(egui::style::Margin::same(8.0));
+        
+        let mut submitted = None;
+        
+        notes_frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
             
-            ui.vertical(|ui| {
-                for (i, patient) in patients.iter().enumerate() {
-                    self.render_patient_card(ui, &patient, i);
-                    ui.add_space(15.0); // Add spacing between cards
+            ui.label(
+                RichText::new(format!("📝 Field notes ({})", patient.field_notes.len()))
+                    .font(FontId::new(12.0, FontFamily::Proportional))
+                    .color(Color32::from_gray(80))
+                    .strong()
+            );
+            
+            let skip = patient.field_notes.len().saturating_sub(FIELD_NOTES_FEED_LEN);
+            for note in patient.field_notes.iter().skip(skip) {
+                ui.label(
+                    RichText::new(format!("{}  {}: {}", self.settings.locale.time(note.at, false), note.author, note.text))
+                        .font(FontId::new(11.0, FontFamily::Proportional))
+                        .color(Color32::from_gray(50))
+                );
+            }
+            
+            ui.add_space(4.0);
+            
+            let draft = self.field_note_drafts.entry(patient.id.clone()).or_default();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::multiline(draft)
+                        .hint_text("Quick update from the field...")
+                        .desired_rows(2)
+                        .desired_width(ui.available_width() - 50.0)
+                );
+                if ui.button("Add").clicked() && !draft.trim().is_empty() {
+                    submitted = Some(draft.trim().to_string());
+                    draft.clear();
                 }
             });
         });
+        
+        if let Some(text) = submitted {
+            let author = self.current_user.name.clone();
+            if let Some(p) = self.touch_patient(&patient.id) {
+                p.merge_field_notes([FieldNote {
+                    at: Local::now(),
+                    author,
+                    text,
+                }]);
+            }
+        }
     }
     
-    fn render_patient_card(&mut self, ui: &mut Ui, patient: &Patient, index: usize) {
-        let triage_color = patient.triage_level.color();
+    fn render_code_panel(&mut self, ui: &mut Ui, patient: &Patient, code: &CodeSession) {
+        let elapsed = code.elapsed_secs(Local::now());
+        let cycle = elapsed / CODE_CYCLE_SECS + 1;
+        let into_cycle = elapsed % CODE_CYCLE_SECS;
         
-        let frame = egui::Frame::none()
-            .fill(Color32::from_gray(245))
-            .stroke(Stroke::new(3.0, triage_color))
-            .rounding(12.0)
-            .inner_margin(egui::style::Margin::same(15.0));
+        // Pulse at the start of every 2-minute cycle after the first
+        let cycle_alert = cycle > 1 && into_cycle < CODE_CYCLE_ALERT_SECS;
+        let fill = if cycle_alert && !self.settings.reduce_motion {
+            let t = ui.input(|i| i.time);
+            ui.ctx().request_repaint();
+            let pulse = (0.5 + 0.5 * (t * 6.0).sin()) as f32;
+            Color32::from_rgb(120 + (111.0 * pulse) as u8, 20, 20)
+        } else if cycle_alert {
+            Color32::from_rgb(231, 76, 60)
+        } else {
+            Color32::from_rgb(120, 20, 20)
+        };
         
-        frame.show(ui, |ui| {
-            ui.set_width(ui.available_width()); // Use full available width
+        let mut intervention = None;
+        let mut ended = false;
+        
+        let code_frame = egui::Frame::none()
+            .fill(fill)
+            .rounding(8.0)
+            .inner_margin(egui::style::Margin::same(12.0));
+        
+        code_frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
             
-            // Patient header
             ui.horizontal(|ui| {
                 ui.label(
-                    RichText::new(&patient.id)
-                        .font(FontId::new(16.0, FontFamily::Proportional))
-                        .color(Color32::from_gray(50))
+                    RichText::new(format!("⚡ CODE  {}", format_mm_ss(elapsed)))
+                        .font(FontId::new(24.0, FontFamily::Monospace))
+                        .color(Color32::WHITE)
                         .strong()
                 );
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    let triage_frame = egui::Frame::none()
-                        .fill(triage_color)
-                        .rounding(20.0)
-                        .inner_margin(egui::style::Margin::symmetric(12.0, 6.0));
-                    
-                    triage_frame.show(ui, |ui| {
-                        ui.label(
-                            RichText::new(patient.triage_level.text())
-                                .font(FontId::new(12.0, FontFamily::Proportional))
-                                .color(Color32::WHITE)
-                                .strong()
-                        );
-                    });
-                });
-            });
-            
-            ui.add_space(10.0);
-            
-            // Patient details - now stacked vertically
-            ui.vertical(|ui| {
-                // Age/Gender
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new("Age/Gender:")
-                            .font(FontId::new(13.0, FontFamily::Proportional))
-                            .color(Color32::from_gray(100))
-                            .strong()
-                    );
-                    ui.label(
-                        RichText::new(format!("{}{}", patient.age, patient.gender))
-                            .font(FontId::new(13.0, FontFamily::Proportional))
-                            .color(Color32::from_gray(50))
-                    );
-                });
-                
-                ui.add_space(5.0);
-                
-                // Chief Complaint
-                ui.horizontal(|ui| {
+                    let cycle_text = if cycle_alert {
+                        "⏰ RHYTHM CHECK".to_string()
+                    } else {
+                        format!("Cycle {} - check in {}", cycle, format_mm_ss(CODE_CYCLE_SECS - into_cycle))
+                    };
                     ui.label(
-                        RichText::new("Chief Complaint:")
+                        RichText::new(cycle_text)
                             .font(FontId::new(13.0, FontFamily::Proportional))
-                            .color(Color32::from_gray(100))
+                            .color(Color32::WHITE)
                             .strong()
                     );
-                    ui.label(
-                        RichText::new(&patient.chief_complaint)
-                            .font(FontId::new(13.0, FontFamily::Proportional))
-                            .color(Color32::from_gray(50))
-                    );
                 });
-                
-                ui.add_space(5.0);
-                
-                // Ambulance (if exists)
-                if let Some(ambulance) = &patient.ambulance_id {
-                    ui.horizontal(|ui| {
-                        ui.label(
-                            RichText::new("Ambulance:")
-                                .font(FontId::new(13.0, FontFamily::Proportional))
-                                .color(Color32::from_gray(100))
-                                .strong()
-                        );
-                        ui.label(
-                            RichText::new(ambulance)
-                                .font(FontId::new(13.0, FontFamily::Proportional))
-                                .color(Color32::from_gray(50))
-                        );
-                    });
-                    ui.add_space(5.0);
+            });
+            
+            ui.add_space(6.0);
+            
+            ui.horizontal_wrapped(|ui| {
+                for name in CODE_INTERVENTIONS {
+                    if ui.button(name).clicked() {
+                        intervention = Some(name);
+                    }
                 }
                 
-                // Paramedic (if exists)
-                if let Some(paramedic) = &patient.paramedic {
-                    ui.horizontal(|ui| {
-                        ui.label(
-                            RichText::new("Paramedic:")
-                                .font(FontId::new(13.0, FontFamily::Proportional))
-                                .color(Color32::from_gray(100))
-                                .strong()
-                        );
-                        ui.label(
-                            RichText::new(paramedic)
-                                .font(FontId::new(13.0, FontFamily::Proportional))
-                                .color(Color32::from_gray(50))
-                        );
-                    });
-                    ui.add_space(5.0);
-                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(RichText::new("End Code").strong()).clicked() {
+                        ended = true;
+                    }
+                });
             });
             
-            ui.add_space(8.0);
-            
-            // Location
-            let location_frame = egui::Frame::none()
-                .fill(Color32::from_rgb(220, 240, 255))
-                .stroke(Stroke::new(1.0, Color32::from_rgb(52, 152, 219)))
-                .rounding(6.0)
-                .inner_margin(egui::style::Margin::same(8.0));
+            for event in code.interventions.iter().rev().take(4) {
+                ui.label(
+                    RichText::new(format!(
+                        "+{}  {}",
+                        format_mm_ss((event.at - code.started_at).num_seconds()),
+                        event.text
+                    ))
+                        .font(FontId::new(11.0, FontFamily::Monospace))
+                        .color(Color32::from_gray(230))
+                );
+            }
+        });
+        
+        if intervention.is_none() && !ended {
+            return;
+        }
+        if let Some(p) = self.touch_patient(&patient.id) {
+            if let Some(name) = intervention {
+                p.log_intervention(name);
+            }
+            if ended {
+                p.end_code();
+            }
+        }
+    }
+    
+    /// Shows chat in a separate OS window. This is an immediate viewport so it can render
+    /// straight from `self` and stay in sync with the docked panel; on backends without
+    /// multi-viewport support it falls back to an in-app window.
+    fn render_chat_viewport(&mut self, ctx: &Context) {
+        let mut open = true;
+        
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("chat_popout"),
+            egui::ViewportBuilder::default()
+                .with_title("💬 Emergency Communication")
+                .with_inner_size([360.0, 640.0]),
+            |ctx, class| {
+                if class == egui::ViewportClass::Embedded {
+                    egui::Window::new("💬 Emergency Communication")
+                        .open(&mut open)
+                        .default_size([320.0, 480.0])
+                        .show(ctx, |ui| {
+                            self.render_chat_panel(ui);
+                        });
+                } else {
+                    CentralPanel::default().show(ctx, |ui| {
+                        self.render_chat_panel(ui);
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        open = false;
+                    }
+                }
+            },
+        );
+        
+        if !open {
+            self.chat_popped_out = false;
+        }
+    }
+    
+    fn render_chat_panel(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        
+        // Chat header
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("💬 EMERGENCY COMMUNICATION")
+                    .font(FontId::new(14.0, FontFamily::Proportional))
+                    .color(Color32::LIGHT_GRAY)
+                    .strong()
+            );
             
-            location_frame.show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("📍");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let (icon, hint) = if self.chat_popped_out {
+                    ("⇲", "Dock chat back into the main window")
+                } else {
+                    ("⇱", "Pop out chat into its own window")
+                };
+                if ui.small_button(icon).on_hover_text(hint).clicked() {
+                    self.chat_popped_out = !self.chat_popped_out;
+                }
+                
+                let notification_frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(231, 76, 60))
+                    .rounding(10.0)
+                    .inner_margin(egui::style::Margin::symmetric(6.0, 3.0));
+                
+                notification_frame.show(ui, |ui| {
                     ui.label(
-                        RichText::new(&patient.location)
-                            .font(FontId::new(12.0, FontFamily::Proportional))
-                            .color(Color32::from_gray(50))
+                        RichText::new("3")
+                            .font(FontId::new(10.0, FontFamily::Proportional))
+                            .color(Color32::WHITE)
+                            .strong()
                     );
                 });
             });
-            
-            ui.add_space(8.0);
-            
-            // Vitals display
-            let vitals_frame = egui::Frame::none()
-                .fill(Color32::from_gray(236))
-                .rounding(8.0)
-                .inner_margin(egui::style::Margin::same(12.0));
-            
-            vitals_frame.show(ui, |ui| {
-                egui::Grid::new(format!("vitals_{}", index))
-                    .num_columns(3)
-                    .spacing([10.0, 0.0])
-                    .show(ui, |ui| {
-                        // Blood pressure
-                        ui.vertical_centered(|ui| {
+        });
+        
+        // Team presence
+        let roster = team_presence(
+            &self.chat_messages,
+            &self.current_user.name,
+            Local::now(),
+            self.settings.presence_online_minutes,
+            self.settings.presence_away_minutes,
+        );
+        ui.add_space(6.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 10.0;
+            for (name, presence) in &roster {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    let (dot, _) = ui.allocate_exact_size(Vec2::splat(8.0), egui::Sense::hover());
+                    ui.painter().circle_filled(dot.center(), 4.0, presence.color());
+                    ui.label(
+                        RichText::new(name)
+                            .font(FontId::new(11.0, FontFamily::Proportional))
+                            .color(Color32::LIGHT_GRAY)
+                    );
+                })
+                    .response
+                    .on_hover_text(presence.text());
+            }
+        });
+        
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+        
+        // Chat messages
+        let mut open_patient = None;
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for message in &self.chat_messages {
+                    let bg_color = if message.urgent {
+                        Color32::from_rgba_premultiplied(231, 76, 60, 30)
+                    } else {
+                        Color32::from_rgb(61, 86, 117)
+                    };
+                    
+                    let stroke = if message.mentions.contains(&self.current_user.role) {
+                        Stroke::new(2.0, Color32::from_rgb(52, 152, 219))
+                    } else if message.urgent {
+                        Stroke::new(2.0, Color32::from_rgb(231, 76, 60))
+                    } else {
+                        Stroke::NONE
+                    };
+                    
+                    let frame = egui::Frame::none()
+                        .fill(bg_color)
+                        .stroke(stroke)
+                        .rounding(8.0)
+                        .inner_margin(egui::style::Margin::same(10.0));
+                    
+                    frame.show(ui, |ui| {
+                        ui.horizontal(|ui| {
                             ui.label(
-                                RichText::new(format!("{}/{}", patient.vitals.blood_pressure.0, patient.vitals.blood_pressure.1))
-                                    .font(FontId::new(18.0, FontFamily::Proportional))
-                                    .color(patient.vitals.bp_status().color())
+                                RichText::new(&message.sender)
+                                    .font(FontId::new(10.0, FontFamily::Proportional))
+                                    .color(Color32::WHITE)
                                     .strong()
                             );
-                            ui.label(
-                                RichText::new("BP")
-                                    .font(FontId::new(11.0, FontFamily::Proportional))
-                                    .color(Color32::from_gray(100))
-                            );
+                            for role in &message.mentions {
+                                ui.label(
+                                    RichText::new(format!("@{}", role.text()))
+                                        .font(FontId::new(10.0, FontFamily::Proportional))
+                                        .color(Color32::from_rgb(52, 152, 219))
+                                        .strong()
+                                );
+                            }
+                            
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.label(
+                                    RichText::new(self.settings.locale.time(message.timestamp, false))
+                                        .font(FontId::new(10.0, FontFamily::Proportional))
+                                        .color(Color32::LIGHT_GRAY)
+                                );
+                            });
                         });
                         
-                        // Heart rate
-                        ui.vertical_centered(|ui| {
-                            ui.label(
-                                RichText::new(format!("{}", patient.vitals.heart_rate))
-                                    .font(FontId::new(18.0, FontFamily::Proportional))
-                                    .color(patient.vitals.hr_status().color())
-                                    .strong()
-                            );
-                            ui.label(
-                                RichText::new("HR")
-                                    .font(FontId::new(11.0, FontFamily::Proportional))
-                                    .color(Color32::from_gray(100))
-                            );
-                        });
+                        ui.add_space(5.0);
                         
-                        // Oxygen saturation
-                        ui.vertical_centered(|ui| {
-                            ui.label(
-                                RichText::new(format!("{}%", patient.vitals.oxygen_saturation))
-                                    .font(FontId::new(18.0, FontFamily::Proportional))
-                                    .color(patient.vitals.o2_status().color())
-                                    .strong()
-                            );
-                            ui.label(
-                                RichText::new("O2 Sat")
-                                    .font(FontId::new(11.0, FontFamily::Proportional))
-                                    .color(Color32::from_gray(100))
-                            );
-                        });
-                    });
-            });
-            
-            ui.add_space(8.0);
-            
-            // ETA display
-            if let Some(eta) = patient.eta_minutes {
-                let eta_frame = egui::Frame::none()
-                    .fill(Color32::from_rgb(52, 152, 219))
-                    .rounding(6.0)
-                    .inner_margin(egui::style::Margin::same(8.0));
-                
-                eta_frame.show(ui, |ui| {
-                    ui.centered_and_justified(|ui| {
                         ui.label(
-                            RichText::new(format!("ETA: {} minutes → Dubai Hospital", eta))
+                            RichText::new(&message.message)
                                 .font(FontId::new(12.0, FontFamily::Proportional))
                                 .color(Color32::WHITE)
-                                .strong()
                         );
+                        
+                        if let Some(attachment) = &message.attachment {
+                            ui.add_space(5.0);
+                            if render_vitals_attachment(ui, attachment, &self.settings.locale) {
+                                open_patient = Some(attachment.patient_id.clone());
+                            }
+                        }
                     });
+                    
+                    ui.add_space(8.0);
+                }
+            });
+        
+        if let Some(patient_id) = open_patient {
+            if self.patients.iter().any(|p| p.id == patient_id) {
+                self.select_patient(&patient_id);
+            }
+        }
+        
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+        
+        // Chat input
+        match self.selected_patient().map(|p| (p.id.clone(), p.awaiting_vitals)) {
+            Some((patient_id, false)) => {
+                ui.toggle_value(&mut self.attach_vitals, format!("📎 Attach vitals for {}", patient_id));
+            }
+            Some((patient_id, true)) => {
+                self.attach_vitals = false;
+                ui.label(
+                    RichText::new(format!("No vitals recorded for {} yet", patient_id))
+                        .font(FontId::new(11.0, FontFamily::Proportional))
+                        .color(Color32::GRAY)
+                );
+            }
+            None => {
+                self.attach_vitals = false;
+                ui.label(
+                    RichText::new("Select a patient to attach their vitals")
+                        .font(FontId::new(11.0, FontFamily::Proportional))
+                        .color(Color32::GRAY)
+                );
+            }
+        }
+        
+        ui.horizontal(|ui| {
+            let text_edit = egui::TextEdit::singleline(&mut self.chat_input)
+                .hint_text("Type emergency message...")
+                .desired_width(ui.available_width() - 60.0);
+            
+            let input = ui.add(text_edit);
+            
+            // Enter sends; Shift+Enter is left free for a future multiline input
+            let enter_pressed = input.lost_focus()
+                && ui.input(|i| i.key_pressed(egui::Key::Enter) && !i.modifiers.shift);
+            
+            let send_clicked = ui.button(
+                RichText::new("Send")
+                    .font(FontId::new(12.0, FontFamily::Proportional))
+                    .color(Color32::WHITE)
+            ).clicked();
+            
+            if send_clicked || enter_pressed {
+                self.send_chat_message();
+            }
+            
+            if enter_pressed {
+                input.request_focus();
+            }
+        });
+    }
+    
+    fn send_chat_message(&mut self) {
+        if self.chat_input.trim().is_empty() {
+            return;
+        }
+        
+        let attachment = self
+            .selected_patient()
+            .filter(|p| self.attach_vitals && !p.awaiting_vitals)
+            .map(|patient| VitalsAttachment {
+                patient_id: patient.id.clone(),
+                vitals: patient.vitals.clone(),
+                captured_at: Local::now(),
+            });
+        
+        let new_message = ChatMessage {
+            id: Uuid::new_v4(),
+            sender: self.current_user.name.clone(),
+            message: self.chat_input.clone(),
+            timestamp: Local::now(),
+            urgent: false,
+            attachment,
+            mentions: vec![],
+            automated: false,
+        };
+        
+        self.chat_messages.push(new_message);
+        self.chat_input.clear();
+        self.attach_vitals = false;
+    }
+    
+    fn render_incoming_patients(&self, ui: &mut Ui) {
+        ui.label("📋 Incoming Patients Dashboard - To be implemented");
+    }
+    
+    fn render_hospital_status(&mut self, ui: &mut Ui) {
+        let mut specialties: Vec<String> = self
+            .hospitals
+            .iter()
+            .flat_map(|h| h.specialties.iter().cloned())
+            .collect();
+        specialties.sort();
+        specialties.dedup();
+        
+        // Filter bar
+        ui.horizontal(|ui| {
+            let in_hospital = self.patients.iter().filter(|p| p.status.is_in_hospital()).count();
+            ui.toggle_value(&mut self.show_bay_board, format!("🛏 Bay Board ({})", in_hospital));
+            
+            ui.add_space(10.0);
+            
+            ui.checkbox(&mut self.hospital_filter.can_accept_now, "✅ Can accept now");
+            
+            ui.add_space(10.0);
+            
+            egui::ComboBox::from_id_source("hospital_specialty_filter")
+                .selected_text(self.hospital_filter.specialty.as_deref().unwrap_or("Any specialty"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.hospital_filter.specialty, None, "Any specialty");
+                    for specialty in specialties {
+                        let label = specialty.clone();
+                        ui.selectable_value(&mut self.hospital_filter.specialty, Some(specialty), label);
+                    }
                 });
+        });
+        
+        ui.add_space(10.0);
+        
+        if self.show_bay_board {
+            self.render_bay_board(ui);
+            return;
+        }
+        
+        let filter = &self.hospital_filter;
+        let visible: Vec<usize> = self
+            .hospitals
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| !filter.can_accept_now || h.can_accept_now(&self.patients, &self.hospitals, &self.settings.routing_rules))
+            .filter(|(_, h)| filter.specialty.as_ref().is_none_or(|s| h.specialties.contains(s)))
+            .map(|(i, _)| i)
+            .collect();
+        
+        if visible.is_empty() {
+            let message = if filter.can_accept_now {
+                "⚠ No hospitals can currently accept"
             } else {
-                let status_frame = egui::Frame::none()
-                    .fill(Color32::from_rgb(52, 152, 219))
-                    .rounding(6.0)
-                    .inner_margin(egui::style::Margin::same(8.0));
+                "No hospitals match the current filters"
+            };
+            ui.label(
+                RichText::new(message)
+                    .font(FontId::new(14.0, FontFamily::Proportional))
+                    .color(Color32::from_rgb(231, 76, 60))
+                    .strong()
+            );
+            return;
+        }
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for i in visible {
+                let hospital = &self.hospitals[i];
+                let inbound = hospital.inbound_count(&self.patients, &self.hospitals, &self.settings.routing_rules);
+                let projected = hospital.projected_available(&self.patients, &self.hospitals, &self.settings.routing_rules);
+                let projected_color = if projected > 0 {
+                    Color32::from_rgb(46, 204, 113)
+                } else {
+                    Color32::from_rgb(231, 76, 60)
+                };
+                let mut on_divert = hospital.on_divert;
                 
-                status_frame.show(ui, |ui| {
-                    ui.centered_and_justified(|ui| {
+                let frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(52, 73, 94))
+                    .rounding(8.0)
+                    .inner_margin(egui::style::Margin::same(12.0));
+                
+                frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    
+                    ui.horizontal(|ui| {
                         ui.label(
-                            RichText::new("Currently in Triage - Room 3")
-                                .font(FontId::new(12.0, FontFamily::Proportional))
+                            RichText::new(&hospital.name)
+                                .font(FontId::new(15.0, FontFamily::Proportional))
                                 .color(Color32::WHITE)
                                 .strong()
                         );
+                        
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.checkbox(&mut on_divert, "On divert");
+                        });
+                    });
+                    
+                    ui.add_space(4.0);
+                    
+                    ui.label(
+                        RichText::new(format!(
+                            "🛏 {}/{} available  •  🚑 {} inbound  •  {} min away",
+                            hospital.available_beds, hospital.total_beds, inbound, hospital.distance_minutes
+                        ))
+                            .font(FontId::new(12.0, FontFamily::Proportional))
+                            .color(Color32::LIGHT_GRAY)
+                    );
+                    ui.label(
+                        RichText::new(format!("Projected availability: {}", projected))
+                            .font(FontId::new(12.0, FontFamily::Proportional))
+                            .color(projected_color)
+                            .strong()
+                    );
+                    ui.horizontal(|ui| {
+                        for capability in Capability::ALL.into_iter().filter(|&c| hospital.has(c)) {
+                            ui.label(RichText::new(capability.icon()).font(FontId::new(14.0, FontFamily::Proportional)))
+                                .on_hover_text(capability.text());
+                        }
+                        ui.label(
+                            RichText::new(hospital.specialties.join(" • "))
+                                .font(FontId::new(11.0, FontFamily::Proportional))
+                                .color(Color32::LIGHT_GRAY)
+                        );
                     });
                 });
-            }
-            
-            ui.add_space(10.0);
-            
-            // Action buttons
-            ui.horizontal(|ui| {
-                if ui.button(
-                    RichText::new("Accept")
-                        .font(FontId::new(12.0, FontFamily::Proportional))
-                        .color(Color32::WHITE)
-                ).clicked() {
-                    // Handle accept action
+                
+                if on_divert != self.hospitals[i].on_divert {
+                    self.hospitals[i].on_divert = on_divert;
+                    self.sidebar_dirty = true;
                 }
                 
-                ui.add_space(8.0);
+                ui.add_space(8.0);
+            }
+        });
+    }
+    
+    /// In-treatment and admitted patients grouped by building and floor.
+    fn render_bay_board(&mut self, ui: &mut Ui) {
+        let mut floors: BTreeMap<(String, String), Vec<&Patient>> = BTreeMap::new();
+        let mut unplaced = Vec::new();
+        for patient in self.patients.iter().filter(|p| p.status.is_in_hospital()) {
+            match &patient.in_hospital_location {
+                Some(location) => floors
+                    .entry((location.building.clone(), location.floor.clone()))
+                    .or_default()
+                    .push(patient),
+                None => unplaced.push(patient),
+            }
+        }
+        
+        if floors.is_empty() && unplaced.is_empty() {
+            ui.label(RichText::new("No patients in treatment or admitted").color(Color32::GRAY));
+            return;
+        }
+        
+        let mut clicked = None;
+        let mut row = |ui: &mut Ui, bay: &str, patient: &Patient| {
+            let is_selected = self.selected_patient.as_deref() == Some(patient.id.as_str());
+            let label = RichText::new(format!(
+                "{}  •  {}  -  {}  ({})",
+                bay,
+                patient.id,
+                patient.chief_complaint,
+                patient.status.text()
+            ))
+                .font(FontId::new(12.0, FontFamily::Proportional))
+                .color(patient.triage_level.color());
+            if ui.selectable_label(is_selected, label).clicked() {
+                clicked = Some(patient.id.clone());
+            }
+        };
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for ((building, floor), patients) in &mut floors {
+                patients.sort_by(|a, b| {
+                    let bay = |p: &Patient| p.in_hospital_location.as_ref().map(|l| l.bay.clone());
+                    bay(a).cmp(&bay(b))
+                });
+                
+                let frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(52, 73, 94))
+                    .rounding(8.0)
+                    .inner_margin(egui::style::Margin::same(12.0));
                 
-                if ui.button(
-                    RichText::new("Call Specialist")
-                        .font(FontId::new(12.0, FontFamily::Proportional))
-                        .color(Color32::WHITE)
-                ).clicked() {
-                    // Handle specialist call
-                }
+                frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(
+                        RichText::new(format!("🏥 {} • Floor {}", building, floor))
+                            .font(FontId::new(14.0, FontFamily::Proportional))
+                            .color(Color32::WHITE)
+                            .strong()
+                    );
+                    ui.add_space(6.0);
+                    for patient in patients.iter() {
+                        let bay = patient.in_hospital_location.as_ref().map_or("", |l| l.bay.as_str());
+                        row(ui, bay, patient);
+                    }
+                });
                 
                 ui.add_space(8.0);
-                
-                if ui.button(
-                    RichText::new("Add Notes")
-                        .font(FontId::new(12.0, FontFamily::Proportional))
-                        .color(Color32::WHITE)
-                ).clicked() {
-                    // Handle notes
+            }
+            
+            if !unplaced.is_empty() {
+                ui.label(
+                    RichText::new("Location not set")
+                        .font(FontId::new(14.0, FontFamily::Proportional))
+                        .color(Color32::from_rgb(243, 156, 18))
+                        .strong()
+                );
+                for patient in unplaced {
+                    row(ui, "-", patient);
                 }
-            });
+            }
         });
+        
+        if let Some(patient_id) = clicked {
+            self.select_patient(&patient_id);
+            self.show_patient_details = true;
+        }
     }
     
-    fn render_chat_panel(&mut self, ui: &mut Ui) {
-        ui.add_space(10.0);
+    /// Writes the shift report for the selected analytics range to the working directory.
+    fn export_shift_report(&mut self) {
+        let end = Local::now();
+        let start = end - self.analytics_range.duration();
+        let patients: Vec<&Patient> = self.patients.iter().chain(self.archived_patients.iter()).collect();
+        let report = shift_report_markdown(&patients, &self.hospitals, start, end);
         
-        // Chat header
+        let path = format!("shift-report-{}.md", end.format("%Y%m%d-%H%M"));
+        match std::fs::write(&path, report) {
+            Ok(()) => self.export_status = Some(format!("Shift report saved to {}", path)),
+            Err(error) => {
+                self.export_status = None;
+                self.push_error(AppError::FileWrite { path, message: error.to_string() });
+            }
+        }
+    }
+    
+    fn render_analytics(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
             ui.label(
-                RichText::new("💬 EMERGENCY COMMUNICATION")
+                RichText::new("📊 ANALYTICS")
                     .font(FontId::new(14.0, FontFamily::Proportional))
                     .color(Color32::LIGHT_GRAY)
                     .strong()
             );
             
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                let notification_frame = egui::Frame::none()
-                    .fill(Color32::from_rgb(231, 76, 60))
-                    .rounding(10.0)
-                    .inner_margin(egui::style::Margin::symmetric(6.0, 3.0));
+                if ui.button("⬇ Export Shift Report").clicked() {
+                    self.export_shift_report();
+                }
                 
-                notification_frame.show(ui, |ui| {
-                    ui.label(
-                        RichText::new("3")
-                            .font(FontId::new(10.0, FontFamily::Proportional))
-                            .color(Color32::WHITE)
-                            .strong()
-                    );
-                });
+                ui.add_space(10.0);
+                
+                for range in AnalyticsRange::ALL.iter().rev() {
+                    if ui.selectable_label(self.analytics_range == *range, range.text()).clicked() {
+                        self.analytics_range = *range;
+                    }
+                }
             });
         });
         
+        if let Some(status) = &self.export_status {
+            ui.label(RichText::new(status).color(Color32::LIGHT_GRAY));
+        }
+        
         ui.add_space(10.0);
-        ui.separator();
+        
+        let end = Local::now();
+        let start = end - self.analytics_range.duration();
+        // Archived patients still count toward history, matching the exported shift report
+        let patients: Vec<&Patient> = self.patients.iter().chain(self.archived_patients.iter()).collect();
+        
+        let frame = egui::Frame::none()
+            .fill(Color32::from_rgb(52, 73, 94))
+            .rounding(8.0)
+            .inner_margin(egui::style::Margin::same(12.0));
+        
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            
+            ui.label(
+                RichText::new("Patient arrivals per 5 min")
+                    .font(FontId::new(12.0, FontFamily::Proportional))
+                    .color(Color32::WHITE)
+                    .strong()
+            );
+            
+            ui.add_space(6.0);
+            
+            let buckets = arrival_buckets(&patients, start, end);
+            paint_arrivals_sparkline(ui, &buckets, start, &self.settings.locale);
+        });
+        
         ui.add_space(10.0);
         
-        // Chat messages
-        egui::ScrollArea::vertical()
-            .stick_to_bottom(true)
-            .show(ui, |ui| {
-                for message in &self.chat_messages {
-                    let bg_color = if message.urgent {
-                        Color32::from_rgba_premultiplied(231, 76, 60, 30)
-                    } else {
-                        Color32::from_rgb(61, 86, 117)
-                    };
-                    
-                    let stroke = if message.urgent {
-                        Stroke::new(2.0, Color32::from_rgb(231, 76, 60))
-                    } else {
-                        Stroke::NONE
-                    };
-                    
-                    let frame = egui::Frame::none()
-                        .fill(bg_color)
-                        .stroke(stroke)
-                        .rounding(8.0)
-                        .inner_margin(egui::style::Margin::same(10.0));
-                    
-                    frame.show(ui, |ui| {
-                        ui.horizontal(|ui| {
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            
+            ui.label(
+                RichText::new("Expected disposition")
+                    .font(FontId::new(12.0, FontFamily::Proportional))
+                    .color(Color32::WHITE)
+                    .strong()
+            );
+            
+            ui.add_space(6.0);
+            
+            let in_range: Vec<&Patient> = patients
+                .iter()
+                .copied()
+                .filter(|p| p.timestamp >= start && p.timestamp <= end)
+                .collect();
+            let total = in_range.len().max(1) as f32;
+            
+            let rows = Disposition::ALL
+                .iter()
+                .map(|d| (d.text(), d.color(), in_range.iter().filter(|p| p.expected_disposition == Some(*d)).count()))
+                .chain(std::iter::once((
+                    "Not set",
+                    Color32::GRAY,
+                    in_range.iter().filter(|p| p.expected_disposition.is_none()).count(),
+                )));
+            
+            egui::Grid::new("disposition_breakdown")
+                .num_columns(3)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    for (label, color, count) in rows {
+                        ui.label(RichText::new(label).color(color));
+                        
+                        let (rect, _) = ui.allocate_exact_size(Vec2::new(200.0, 12.0), egui::Sense::hover());
+                        ui.painter().rect_filled(rect, 3.0, Color32::from_gray(70));
+                        let mut filled = rect;
+                        filled.set_width(rect.width() * count as f32 / total);
+                        ui.painter().rect_filled(filled, 3.0, color);
+                        
+                        ui.label(RichText::new(count.to_string()).color(Color32::WHITE));
+                        ui.end_row();
+                    }
+                });
+        });
+        
+        ui.add_space(10.0);
+        
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new("Board acuity mix")
+                        .font(FontId::new(12.0, FontFamily::Proportional))
+                        .color(Color32::WHITE)
+                        .strong()
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    for level in TriageLevel::ALL.iter().rev() {
+                        ui.label(RichText::new(level.text()).font(FontId::new(10.0, FontFamily::Proportional)).color(level.color()));
+                    }
+                });
+            });
+            
+            ui.add_space(6.0);
+            
+            let samples = acuity_mix(&patients, start, end);
+            paint_acuity_chart(ui, &samples, start, end, &self.settings.locale);
+        });
+    }
+    
+    /// MCI declare/stand-down controls and the incident report.
+    fn render_incident_controls(&mut self, ui: &mut Ui) {
+        let can_command = self.current_user.role.can_broadcast();
+        let active = self.incident.as_ref().is_some_and(|i| i.is_active());
+        
+        let frame = egui::Frame::none()
+            .fill(Color32::from_rgb(52, 73, 94))
+            .rounding(8.0)
+            .inner_margin(egui::style::Margin::same(12.0));
+        
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            
+            ui.horizontal(|ui| {
+                let (text, color) = if active {
+                    ("🚨 MCI ACTIVE", Color32::from_rgb(231, 76, 60))
+                } else {
+                    ("No major incident declared", Color32::LIGHT_GRAY)
+                };
+                ui.label(
+                    RichText::new(text)
+                        .font(FontId::new(16.0, FontFamily::Proportional))
+                        .color(color)
+                        .strong()
+                );
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let label = if active { "Stand Down" } else { "Declare MCI" };
+                    let clicked = ui
+                        .add_enabled(can_command, egui::Button::new(RichText::new(label).strong()))
+                        .on_disabled_hover_text("Only an ER Director or Charge Nurse can declare or stand down an MCI")
+                        .clicked();
+                    if clicked {
+                        let by = self.current_user.name.clone();
+                        match self.incident.as_mut().filter(|i| i.is_active()) {
+                            Some(incident) => incident.stand_down(&by),
+                            None => self.incident = Some(Incident::declare(&by)),
+                        }
+                    }
+                });
+            });
+            
+            if let Some(incident) = &self.incident {
+                ui.add_space(6.0);
+                egui::CollapsingHeader::new(
+                    RichText::new(format!(
+                        "Incident report - {} ({})",
+                        self.settings.locale.date_time(incident.declared_at),
+                        format_hh_mm_ss(incident.elapsed_secs(Local::now()))
+                    ))
+                        .color(Color32::WHITE)
+                )
+                    .id_source("incident_report")
+                    .show(ui, |ui| {
+                        for event in &incident.log {
                             ui.label(
-                                RichText::new(&message.sender)
-                                    .font(FontId::new(10.0, FontFamily::Proportional))
-                                    .color(Color32::WHITE)
-                                    .strong()
+                                RichText::new(format!("{}  {}", self.settings.locale.time(event.at, true), event.text))
+                                    .font(FontId::new(11.0, FontFamily::Proportional))
+                                    .color(Color32::LIGHT_GRAY)
                             );
-                            
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        }
+                    });
+            }
+        });
+    }
+    
+    fn render_incident_command(&mut self, ui: &mut Ui) {
+        // Bucket patients by scene zone, keeping their index for selection
+        let mut zones: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, patient) in self.patients.iter().enumerate() {
+            zones.entry(patient.zone(&self.zone_rules)).or_default().push(i);
+        }
+
+        self.render_incident_controls(ui);
+        ui.add_space(10.0);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (zone, indices) in &zones {
+                let frame = egui::Frame::none()
+                    .fill(Color32::from_rgb(52, 73, 94))
+                    .rounding(8.0)
+                    .inner_margin(egui::style::Margin::same(12.0));
+
+                frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+
+                    // Zone header
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(format!("🗺 {}", zone))
+                                .font(FontId::new(16.0, FontFamily::Proportional))
+                                .color(Color32::WHITE)
+                                .strong()
+                        );
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(
+                                RichText::new(format!("{} patients", indices.len()))
+                                    .font(FontId::new(12.0, FontFamily::Proportional))
+                                    .color(Color32::LIGHT_GRAY)
+                            );
+                        });
+                    });
+
+                    ui.add_space(8.0);
+
+                    // Triage summary
+                    ui.horizontal(|ui| {
+                        for level in TriageLevel::ALL {
+                            let count = indices
+                                .iter()
+                                .filter(|&&i| self.patients[i].triage_level == level)
+                                .count();
+
+                            let chip = egui::Frame::none()
+                                .fill(if count > 0 { level.color() } else { Color32::from_gray(90) })
+                                .rounding(10.0)
+                                .inner_margin(egui::style::Margin::symmetric(8.0, 3.0));
+
+                            chip.show(ui, |ui| {
                                 ui.label(
-                                    RichText::new(message.timestamp.format("%H:%M").to_string())
-                                        .font(FontId::new(10.0, FontFamily::Proportional))
-                                        .color(Color32::LIGHT_GRAY)
+                                    RichText::new(format!("{} {}", count, level.text()))
+                                        .font(FontId::new(11.0, FontFamily::Proportional))
+                                        .color(Color32::WHITE)
+                                        .strong()
                                 );
                             });
+                        }
+                    });
+
+                    ui.add_space(6.0);
+
+                    // Resource counts
+                    let ambulances = indices
+                        .iter()
+                        .filter(|&&i| self.patients[i].ambulance_id.is_some())
+                        .count();
+                    let paramedics = indices
+                        .iter()
+                        .filter(|&&i| self.patients[i].paramedic.is_some())
+                        .count();
+                    let longest_wait = indices
+                        .iter()
+                        .map(|&i| (Local::now() - self.patients[i].timestamp).num_minutes())
+                        .max()
+                        .unwrap_or(0);
+
+                    ui.label(
+                        RichText::new(self.settings.locale.digits(&format!(
+                            "🚑 {} ambulances  •  👨‍⚕️ {} paramedics  •  ⏱ longest wait {} min",
+                            ambulances, paramedics, longest_wait
+                        )))
+                            .font(FontId::new(11.0, FontFamily::Proportional))
+                            .color(Color32::LIGHT_GRAY)
+                    );
+
+                    ui.add_space(6.0);
+
+                    // Patients in this zone
+                    for &i in indices {
+                        let patient = &self.patients[i];
+                        let is_selected = self.selected_patient.as_deref() == Some(patient.id.as_str());
+                        let label = RichText::new(format!(
+                            "{}  {}  -  {}",
+                            patient.triage_level.text(),
+                            patient.id,
+                            patient.chief_complaint
+                        ))
+                            .font(FontId::new(12.0, FontFamily::Proportional))
+                            .color(patient.triage_level.color());
+
+                        if ui.selectable_label(is_selected, label).clicked() {
+                            let patient_id = patient.id.clone();
+                            self.select_patient(&patient_id);
+                        }
+                    }
+                });
+
+                ui.add_space(10.0);
+            }
+
+            ui.add_space(10.0);
+
+            // Zone mapping editor
+            egui::CollapsingHeader::new("Zone mapping")
+                .id_source("zone_mapping")
+                .show(ui, |ui| {
+                    let mut removed = None;
+
+                    egui::Grid::new("zone_rules")
+                        .num_columns(3)
+                        .spacing([10.0, 4.0])
+                        .show(ui, |ui| {
+                            for (i, rule) in self.zone_rules.iter_mut().enumerate() {
+                                ui.text_edit_singleline(&mut rule.keyword);
+                                ui.text_edit_singleline(&mut rule.zone);
+                                if ui.small_button("✖").clicked() {
+                                    removed = Some(i);
+                                }
+                                ui.end_row();
+                            }
                         });
-                        
-                        ui.add_space(5.0);
-                        
-                        ui.label(
-                            RichText::new(&message.message)
-                                .font(FontId::new(12.0, FontFamily::Proportional))
-                                .color(Color32::WHITE)
+
+                    if let Some(i) = removed {
+                        self.zone_rules.remove(i);
+                    }
+
+                    ui.add_space(5.0);
+
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_zone_keyword)
+                                .hint_text("Location keyword")
+                                .desired_width(160.0)
                         );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_zone_name)
+                                .hint_text("Zone")
+                                .desired_width(100.0)
+                        );
+
+                        if ui.button("Add").clicked()
+                            && !self.new_zone_keyword.trim().is_empty()
+                            && !self.new_zone_name.trim().is_empty()
+                        {
+                            self.zone_rules.push(ZoneRule {
+                                keyword: self.new_zone_keyword.trim().to_string(),
+                                zone: self.new_zone_name.trim().to_string(),
+                            });
+                            self.new_zone_keyword.clear();
+                            self.new_zone_name.clear();
+                        }
                     });
-                    
-                    ui.add_space(8.0);
-                }
+                });
+        });
+    }
+}
+
+/// Compact, color-coded vitals snapshot shown inside a chat message. Returns true when the
+/// patient id is clicked.
+fn render_vitals_attachment(ui: &mut Ui, attachment: &VitalsAttachment, locale: &LocaleSettings) -> bool {
+    let vitals = &attachment.vitals;
+    let mut clicked = false;
+    
+    egui::Frame::none()
+        .fill(Color32::from_rgb(44, 62, 80))
+        .rounding(6.0)
+        .inner_margin(egui::style::Margin::same(6.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                clicked = ui
+                    .link(RichText::new(format!("📎 {}", attachment.patient_id)).color(Color32::WHITE).strong())
+                    .on_hover_text("Select patient")
+                    .clicked();
+                ui.label(
+                    RichText::new(locale.time(attachment.captured_at, false))
+                        .font(FontId::new(10.0, FontFamily::Proportional))
+                        .color(Color32::LIGHT_GRAY)
+                );
             });
-        
-        ui.add_space(10.0);
-        ui.separator();
-        ui.add_space(10.0);
-        
-        // Chat input
-        ui.horizontal(|ui| {
-            let text_edit = egui::TextEdit::singleline(&mut self.chat_input)
-                .hint_text("Type emergency message...")
-                .desired_width(ui.available_width() - 60.0);
-            
-            ui.add(text_edit);
-            
-            if ui.button(
-                RichText::new("Send")
-                    .font(FontId::new(12.0, FontFamily::Proportional))
-                    .color(Color32::WHITE)
-            ).clicked() {
-                if !self.chat_input.trim().is_empty() {
-                    let new_message = ChatMessage {
-                        id: Uuid::new_v4(),
-                        sender: "Dr. Ahmed Al-Mansoori".to_string(),
-                        message: self.chat_input.clone(),
-                        timestamp: Local::now(),
-                        urgent: false,
-                    };
-                    
-                    self.chat_messages.push(new_message);
-                    self.chat_input.clear();
+            ui.horizontal_wrapped(|ui| {
+                let values = [
+                    (format!("BP {}/{}", vitals.blood_pressure.0, vitals.blood_pressure.1), vitals.bp_status()),
+                    (format!("HR {}", vitals.heart_rate), vitals.hr_status()),
+                    (format!("O2 {}%", vitals.oxygen_saturation), vitals.o2_status()),
+                    (format!("T {:.1}°", vitals.temperature), vitals.temp_status()),
+                ];
+                for (text, status) in values {
+                    ui.label(
+                        RichText::new(locale.digits(&text))
+                            .font(FontId::new(11.0, FontFamily::Proportional))
+                            .color(status.color())
+                            .strong()
+                    );
                 }
+            });
+        });
+    
+    clicked
+}
+
+/// One dot per vital (BP, HR, O2, Temp) colored by its status; hover for the values.
+fn paint_vitals_dots(ui: &mut Ui, vitals: &VitalSigns) {
+    let statuses = [vitals.bp_status(), vitals.hr_status(), vitals.o2_status(), vitals.temp_status()];
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(64.0, 14.0), egui::Sense::hover());
+    
+    for (i, status) in statuses.iter().enumerate() {
+        let center = egui::pos2(rect.left() + 7.0 + i as f32 * 16.0, rect.center().y);
+        ui.painter().circle_filled(center, 5.0, status.color());
+    }
+    
+    response.on_hover_text(format!(
+        "BP {}/{}\nHR {} bpm\nO2 {}%\nTemp {:.1} °C",
+        vitals.blood_pressure.0, vitals.blood_pressure.1, vitals.heart_rate, vitals.oxygen_saturation, vitals.temperature
+    ));
+}
+
+/// Seconds a stepper button must be held before it starts repeating, and the repeat interval.
+const STEPPER_REPEAT_DELAY: f64 = 0.5;
+const STEPPER_REPEAT_INTERVAL: f64 = 0.08;
+
+/// A button that fires once on click and repeatedly while held down.
+fn repeat_button(ui: &mut Ui, text: &str) -> bool {
+    let response = ui.button(text);
+    if response.clicked() {
+        return true;
+    }
+    if !response.is_pointer_button_down_on() {
+        return false;
+    }
+    
+    ui.ctx().request_repaint();
+    let (now, pressed_at) = ui.input(|i| (i.time, i.pointer.press_start_time()));
+    let Some(pressed_at) = pressed_at else {
+        return false;
+    };
+    if now - pressed_at < STEPPER_REPEAT_DELAY {
+        return false;
+    }
+    
+    let last_fired = ui.data(|d| d.get_temp::<f64>(response.id)).unwrap_or(pressed_at);
+    if now - last_fired >= STEPPER_REPEAT_INTERVAL {
+        ui.data_mut(|d| d.insert_temp(response.id, now));
+        return true;
+    }
+    false
+}
+
+/// Direct entry with -/+ buttons for each step size, e.g. `&[1, 5]` for ±1 and ±5.
+fn int_stepper(ui: &mut Ui, value: &mut i32, steps: &[i32], bounds: std::ops::RangeInclusive<i32>, suffix: &str) {
+    ui.horizontal(|ui| {
+        for step in steps.iter().rev() {
+            if repeat_button(ui, &format!("−{}", step)) {
+                *value -= step;
             }
-        });
+        }
+        ui.add(egui::DragValue::new(value).clamp_range(bounds).suffix(suffix));
+        for step in steps {
+            if repeat_button(ui, &format!("+{}", step)) {
+                *value += step;
+            }
+        }
+    });
+}
+
+/// Draws a heart-rate trend across whatever history window is currently retained.
+fn paint_heart_rate_history(ui: &mut Ui, history: &[VitalsSample], locale: &LocaleSettings) {
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 28.0), egui::Sense::hover());
+    
+    let min = history.iter().map(|s| s.vitals.heart_rate).min().unwrap_or(0) as f32;
+    let max = history.iter().map(|s| s.vitals.heart_rate).max().unwrap_or(0) as f32;
+    let span = (max - min).max(1.0);
+    let step = rect.width() / (history.len() - 1) as f32;
+    
+    let points: Vec<egui::Pos2> = history
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let t = (sample.vitals.heart_rate as f32 - min) / span;
+            egui::pos2(rect.left() + i as f32 * step, rect.bottom() - t * rect.height())
+        })
+        .collect();
+    
+    ui.painter().add(egui::Shape::line(points, Stroke::new(1.5, Color32::from_rgb(52, 152, 219))));
+    
+    if let (Some(first), Some(last)) = (history.first(), history.last()) {
+        response.on_hover_text(format!(
+            "HR {}–{} bpm, {} samples from {} to {}",
+            min,
+            max,
+            history.len(),
+            locale.time(first.recorded_at, true),
+            locale.time(last.recorded_at, true)
+        ));
     }
+}
+
+/// Counts patient arrivals in consecutive `ARRIVAL_BUCKET_MINUTES` windows from `start` to `end`.
+fn arrival_buckets(patients: &[&Patient], start: DateTime<Local>, end: DateTime<Local>) -> Vec<usize> {
+    let bucket_secs = ARRIVAL_BUCKET_MINUTES * 60;
+    let count = ((end - start).num_seconds() + bucket_secs - 1) / bucket_secs;
+    let mut buckets = vec![0; count.max(1) as usize];
     
-    fn render_incoming_patients(&self, ui: &mut Ui) {
-        ui.label("📋 Incoming Patients Dashboard - To be implemented");
+    for patient in patients {
+        if patient.timestamp < start || patient.timestamp > end {
+            continue;
+        }
+        let last = buckets.len() - 1;
+        let index = ((patient.timestamp - start).num_seconds() / bucket_secs) as usize;
+        buckets[index.min(last)] += 1;
+    }
+    
+    buckets
+}
+
+/// Points sampled across the range for the acuity mix chart.
+const ACUITY_SAMPLES: usize = 48;
+
+/// Board counts per triage level (in `TriageLevel::ALL` order) at evenly spaced points from
+/// `start` to `end`. With no triage changes in the range the current mix is repeated flat.
+fn acuity_mix(patients: &[&Patient], start: DateTime<Local>, end: DateTime<Local>) -> Vec<[usize; TriageLevel::ALL.len()]> {
+    let count_at = |at: DateTime<Local>| {
+        let mut counts = [0; TriageLevel::ALL.len()];
+        for patient in patients.iter().filter(|p| p.on_board_at(at)) {
+            let level = patient.triage_at(at);
+            counts[TriageLevel::ALL.iter().position(|l| *l == level).unwrap_or(0)] += 1;
+        }
+        counts
+    };
+    
+    let changed_in_range = patients
+        .iter()
+        .flat_map(|p| &p.triage_history)
+        .any(|change| change.at >= start && change.at <= end);
+    if !changed_in_range {
+        return vec![count_at(end); ACUITY_SAMPLES];
+    }
+    
+    let step = (end - start) / (ACUITY_SAMPLES as i32 - 1);
+    (0..ACUITY_SAMPLES).map(|i| count_at(start + step * i as i32)).collect()
+}
+
+/// Stacked area chart of `acuity_mix` samples, Critical at the bottom.
+fn paint_acuity_chart(
+    ui: &mut Ui,
+    samples: &[[usize; TriageLevel::ALL.len()]],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    locale: &LocaleSettings,
+) {
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 120.0), egui::Sense::hover());
+    let painter = ui.painter();
+    
+    let max = samples.iter().map(|counts| counts.iter().sum::<usize>()).max().unwrap_or(0);
+    painter.line_segment([rect.left_bottom(), rect.right_bottom()], Stroke::new(1.0, Color32::from_gray(90)));
+    if max == 0 || samples.len() < 2 {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "no patients on the board",
+            FontId::new(12.0, FontFamily::Proportional),
+            Color32::LIGHT_GRAY,
+        );
+        return;
+    }
+    
+    let step = rect.width() / (samples.len() - 1) as f32;
+    let x = |i: usize| rect.left() + i as f32 * step;
+    let y = |count: usize| rect.bottom() - count as f32 / max as f32 * rect.height();
+    
+    for (level_index, level) in TriageLevel::ALL.iter().enumerate() {
+        for i in 0..samples.len() - 1 {
+            let below = |counts: &[usize; TriageLevel::ALL.len()]| counts[..level_index].iter().sum::<usize>();
+            let (left, right) = (&samples[i], &samples[i + 1]);
+            let quad = vec![
+                egui::pos2(x(i), y(below(left))),
+                egui::pos2(x(i), y(below(left) + left[level_index])),
+                egui::pos2(x(i + 1), y(below(right) + right[level_index])),
+                egui::pos2(x(i + 1), y(below(right))),
+            ];
+            painter.add(egui::Shape::convex_polygon(quad, level.color(), Stroke::NONE));
+        }
+    }
+    
+    if let Some(pointer) = response.hover_pos() {
+        let index = (((pointer.x - rect.left()) / step).round() as usize).min(samples.len() - 1);
+        painter.line_segment([egui::pos2(x(index), rect.top()), egui::pos2(x(index), rect.bottom())], Stroke::new(1.0, Color32::WHITE));
+        
+        let at = start + (end - start) / (samples.len() as i32 - 1) * index as i32;
+        let breakdown: Vec<String> = TriageLevel::ALL
+            .iter()
+            .zip(samples[index])
+            .map(|(level, count)| format!("{} {}", level.text(), count))
+            .collect();
+        response.on_hover_text(format!("{}: {}", locale.time(at, false), breakdown.join(" • ")));
+    }
+}
+
+/// Aggregate statistics for patients who arrived in `start..=end`, as markdown. Empty
+/// ranges report zeros. Wait is arrival to acceptance, or to `end` if not yet accepted;
+/// accepted patients with no recorded acceptance time are left out. Divert status is a
+/// snapshot at export, not a history of the shift.
+fn shift_report_markdown(
+    patients: &[&Patient],
+    hospitals: &[Hospital],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> String {
+    use std::fmt::Write;
+    
+    let in_range: Vec<&Patient> = patients
+        .iter()
+        .copied()
+        .filter(|p| p.timestamp >= start && p.timestamp <= end)
+        .collect();
+    let average = |values: &[i64]| {
+        if values.is_empty() {
+            0.0
+        } else {
+            values.iter().sum::<i64>() as f64 / values.len() as f64
+        }
+    };
+    
+    let etas: Vec<i64> = in_range.iter().filter_map(|p| p.eta_minutes).map(i64::from).collect();
+    let waits: Vec<i64> = in_range
+        .iter()
+        .filter_map(|p| {
+            let until = if p.accepted { p.accepted_at? } else { end };
+            Some((until - p.timestamp).num_minutes().max(0))
+        })
+        .collect();
+    let urgent: Vec<&&Patient> = in_range
+        .iter()
+        .filter(|p| matches!(p.triage_level, TriageLevel::Critical | TriageLevel::High))
+        .collect();
+    let urgent_accepted = urgent.iter().filter(|p| p.accepted).count();
+    let on_divert: Vec<&str> = hospitals.iter().filter(|h| h.on_divert).map(|h| h.name.as_str()).collect();
+    
+    let mut report = String::new();
+    let _ = writeln!(report, "# Shift Report");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "{} to {}", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M"));
+    let _ = writeln!(report);
+    
+    let _ = writeln!(report, "## Patients by triage");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "| Triage | Patients |");
+    let _ = writeln!(report, "|---|---|");
+    for level in TriageLevel::ALL {
+        let count = in_range.iter().filter(|p| p.triage_level == level).count();
+        let _ = writeln!(report, "| {} | {} |", level.text(), count);
+    }
+    let _ = writeln!(report, "| **Total** | {} |", in_range.len());
+    let _ = writeln!(report);
+    
+    let _ = writeln!(report, "## Flow");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "- Average ETA: {:.1} min", average(&etas));
+    let _ = writeln!(report, "- Average wait: {:.1} min", average(&waits));
+    let _ = writeln!(report, "- Max wait: {} min", waits.iter().max().copied().unwrap_or(0));
+    let _ = writeln!(report, "- Urgent (Critical/High) accepted: {}", urgent_accepted);
+    let _ = writeln!(report, "- Urgent (Critical/High) not yet accepted: {}", urgent.len() - urgent_accepted);
+    let _ = writeln!(
+        report,
+        "- Hospitals on divert at export: {}{}",
+        on_divert.len(),
+        if on_divert.is_empty() { String::new() } else { format!(" ({})", on_divert.join(", ")) }
+    );
+    let _ = writeln!(report);
+    
+    let _ = writeln!(report, "## Arrivals per hour");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "| Hour starting | Arrivals |");
+    let _ = writeln!(report, "|---|---|");
+    let mut hour = start;
+    while hour < end {
+        let next = hour + chrono::Duration::hours(1);
+        let count = in_range.iter().filter(|p| p.timestamp >= hour && p.timestamp < next).count();
+        let _ = writeln!(report, "| {} | {} |", hour.format("%H:%M"), count);
+        hour = next;
     }
     
-    fn render_hospital_status(&self, ui: &mut Ui) {
-        ui.label("🏥 Hospital Status Dashboard - To be implemented");
+    report
+}
+
+fn paint_arrivals_sparkline(ui: &mut Ui, buckets: &[usize], start: DateTime<Local>, locale: &LocaleSettings) {
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 60.0), egui::Sense::hover());
+    let painter = ui.painter();
+    let line_color = Color32::from_rgb(52, 152, 219);
+    
+    let max = buckets.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        painter.line_segment([rect.left_bottom(), rect.right_bottom()], Stroke::new(1.5, Color32::GRAY));
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "no arrivals",
+            FontId::new(12.0, FontFamily::Proportional),
+            Color32::LIGHT_GRAY,
+        );
+        return;
     }
     
-    fn render_analytics(&self, ui: &mut Ui) {
-        ui.label("📊 Analytics Dashboard - To be implemented");
+    let step = rect.width() / buckets.len() as f32;
+    let points: Vec<egui::Pos2> = buckets
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let t = count as f32 / max as f32;
+            egui::pos2(rect.left() + (i as f32 + 0.5) * step, rect.bottom() - t * rect.height())
+        })
+        .collect();
+    
+    painter.line_segment([rect.left_bottom(), rect.right_bottom()], Stroke::new(1.0, Color32::from_gray(90)));
+    painter.add(egui::Shape::line(points.clone(), Stroke::new(2.0, line_color)));
+    
+    if let Some(pointer) = response.hover_pos() {
+        let index = (((pointer.x - rect.left()) / step) as usize).min(buckets.len() - 1);
+        painter.circle_filled(points[index], 4.0, Color32::WHITE);
+        
+        let bucket_start = start + chrono::Duration::minutes(index as i64 * ARRIVAL_BUCKET_MINUTES);
+        let bucket_end = bucket_start + chrono::Duration::minutes(ARRIVAL_BUCKET_MINUTES);
+        response.on_hover_text(format!(
+            "{}–{}: {} arrival{}",
+            locale.time(bucket_start, false),
+            locale.time(bucket_end, false),
+            buckets[index],
+            if buckets[index] == 1 { "" } else { "s" }
+        ));
     }
 }
 
@@ -891,6 +7601,7 @@ fn create_demo_patients() -> Vec<Patient> {
             gender: "M".to_string(),
             chief_complaint: "Chest Pain".to_string(),
             triage_level: TriageLevel::Critical,
+            vitals_history: vec![],
             vitals: VitalSigns {
                 blood_pressure: (180, 120),
                 heart_rate: 45,
@@ -898,11 +7609,31 @@ fn create_demo_patients() -> Vec<Patient> {
                 temperature: 37.2,
             },
             location: "Sheikh Zayed Road, near DIFC Metro Station".to_string(),
+            zone: None,
             eta_minutes: Some(7),
             ambulance_id: Some("AMB-DXB-047".to_string()),
             paramedic: Some("Hassan Al-Rashid".to_string()),
+            assigned_hospital: Some("Dubai Hospital".to_string()),
             notes: vec![],
-            timestamp: Local::now(),
+            timestamp: Local::now() - chrono::Duration::minutes(4),
+            last_updated: Local::now(),
+            last_triage_at: Local::now() - chrono::Duration::minutes(4),
+            accepted: false,
+            timeline: vec![],
+            code: None,
+            last_touched: None,
+            expected_disposition: None,
+            field_notes: vec![],
+            status: PatientStatus::EnRoute,
+            status_changed_at: None,
+            triage_needs_review: false,
+            orders: vec![],
+            triage_history: vec![],
+            awaiting_vitals: false,
+            eta_set_at: None,
+            in_hospital_location: None,
+            accepted_at: None,
+            ambulance_released: false,
         },
         Patient {
             id: "PATIENT-002".to_string(),
@@ -910,6 +7641,7 @@ fn create_demo_patients() -> Vec<Patient> {
             gender: "F".to_string(),
             chief_complaint: "Motor Vehicle Accident".to_string(),
             triage_level: TriageLevel::High,
+            vitals_history: vec![],
             vitals: VitalSigns {
                 blood_pressure: (140, 85),
                 heart_rate: 95,
@@ -917,11 +7649,31 @@ fn create_demo_patients() -> Vec<Patient> {
                 temperature: 36.8,
             },
             location: "Al Khaleej Road, near Dubai Mall".to_string(),
+            zone: None,
             eta_minutes: Some(12),
             ambulance_id: Some("AMB-DXB-112".to_string()),
             paramedic: Some("Fatima Al-Zahra".to_string()),
+            assigned_hospital: Some("Dubai Hospital".to_string()),
             notes: vec![],
-            timestamp: Local::now(),
+            timestamp: Local::now() - chrono::Duration::minutes(9),
+            last_updated: Local::now(),
+            last_triage_at: Local::now() - chrono::Duration::minutes(9),
+            accepted: false,
+            timeline: vec![],
+            code: None,
+            last_touched: None,
+            expected_disposition: None,
+            field_notes: vec![],
+            status: PatientStatus::EnRoute,
+            status_changed_at: None,
+            triage_needs_review: false,
+            orders: vec![],
+            triage_history: vec![],
+            awaiting_vitals: false,
+            eta_set_at: None,
+            in_hospital_location: None,
+            accepted_at: None,
+            ambulance_released: false,
         },
         Patient {
             id: "PATIENT-003".to_string(),
@@ -929,6 +7681,7 @@ fn create_demo_patients() -> Vec<Patient> {
             gender: "M".to_string(),
             chief_complaint: "Respiratory Distress".to_string(),
             triage_level: TriageLevel::Medium,
+            vitals_history: vec![],
             vitals: VitalSigns {
                 blood_pressure: (110, 70),
                 heart_rate: 125,
@@ -936,11 +7689,31 @@ fn create_demo_patients() -> Vec<Patient> {
                 temperature: 38.5,
             },
             location: "Jumeirah Beach Road, near Jumeirah Beach".to_string(),
+            zone: None,
             eta_minutes: Some(18),
             ambulance_id: Some("AMB-DXB-093".to_string()),
             paramedic: Some("John Mitchell".to_string()),
+            assigned_hospital: None,
             notes: vec![],
-            timestamp: Local::now(),
+            timestamp: Local::now() - chrono::Duration::minutes(23),
+            last_updated: Local::now() - chrono::Duration::minutes(5),
+            last_triage_at: Local::now() - chrono::Duration::minutes(23),
+            accepted: false,
+            timeline: vec![],
+            code: None,
+            last_touched: None,
+            expected_disposition: None,
+            field_notes: vec![],
+            status: PatientStatus::EnRoute,
+            status_changed_at: None,
+            triage_needs_review: false,
+            orders: vec![],
+            triage_history: vec![],
+            awaiting_vitals: false,
+            eta_set_at: None,
+            in_hospital_location: None,
+            accepted_at: None,
+            ambulance_released: false,
         },
         Patient {
             id: "PATIENT-004".to_string(),
@@ -948,6 +7721,7 @@ fn create_demo_patients() -> Vec<Patient> {
             gender: "F".to_string(),
             chief_complaint: "Minor Laceration".to_string(),
             triage_level: TriageLevel::Low,
+            vitals_history: vec![],
             vitals: VitalSigns {
                 blood_pressure: (120, 80),
                 heart_rate: 72,
@@ -955,11 +7729,52 @@ fn create_demo_patients() -> Vec<Patient> {
                 temperature: 36.5,
             },
             location: "Dubai Hospital - Triage Room 3".to_string(),
+            zone: None,
             eta_minutes: None,
             ambulance_id: None,
             paramedic: None,
+            assigned_hospital: Some("Dubai Hospital".to_string()),
             notes: vec![],
-            timestamp: Local::now(),
+            timestamp: Local::now() - chrono::Duration::minutes(41),
+            last_updated: Local::now(),
+            last_triage_at: Local::now() - chrono::Duration::minutes(41),
+            accepted: false,
+            timeline: vec![],
+            code: None,
+            last_touched: None,
+            expected_disposition: None,
+            field_notes: vec![],
+            status: PatientStatus::InTriage,
+            status_changed_at: None,
+            triage_needs_review: false,
+            orders: vec![],
+            triage_history: vec![],
+            awaiting_vitals: false,
+            eta_set_at: None,
+            in_hospital_location: None,
+            accepted_at: None,
+            ambulance_released: false,
+        },
+    ]
+}
+
+fn create_default_zone_rules() -> Vec<ZoneRule> {
+    vec![
+        ZoneRule {
+            keyword: "Sheikh Zayed Road".to_string(),
+            zone: "Zone A".to_string(),
+        },
+        ZoneRule {
+            keyword: "Al Khaleej Road".to_string(),
+            zone: "Zone B".to_string(),
+        },
+        ZoneRule {
+            keyword: "Jumeirah".to_string(),
+            zone: "Zone C".to_string(),
+        },
+        ZoneRule {
+            keyword: "Hospital".to_string(),
+            zone: "In Hospital".to_string(),
         },
     ]
 }
@@ -972,6 +7787,12 @@ fn create_demo_hospitals() -> Vec<Hospital> {
             total_beds: 25,
             distance_minutes: 12,
             specialties: vec!["Emergency Medicine".to_string(), "Cardiology".to_string()],
+            on_divert: false,
+            trauma_center: false,
+            stroke_ready: false,
+            cath_lab: true,
+            pediatric: false,
+            burn_unit: false,
         },
         Hospital {
             name: "Rashid Hospital".to_string(),
@@ -979,6 +7800,12 @@ fn create_demo_hospitals() -> Vec<Hospital> {
             total_beds: 30,
             distance_minutes: 8,
             specialties: vec!["Trauma Surgery".to_string(), "Neurology".to_string()],
+            on_divert: false,
+            trauma_center: true,
+            stroke_ready: true,
+            cath_lab: false,
+            pediatric: false,
+            burn_unit: true,
         },
         Hospital {
             name: "American Hospital".to_string(),
@@ -986,6 +7813,12 @@ fn create_demo_hospitals() -> Vec<Hospital> {
             total_beds: 20,
             distance_minutes: 15,
             specialties: vec!["General Medicine".to_string(), "Pediatrics".to_string()],
+            on_divert: false,
+            trauma_center: false,
+            stroke_ready: false,
+            cath_lab: false,
+            pediatric: true,
+            burn_unit: false,
         },
         Hospital {
             name: "NMC Healthcare".to_string(),
@@ -993,6 +7826,12 @@ fn create_demo_hospitals() -> Vec<Hospital> {
             total_beds: 18,
             distance_minutes: 20,
             specialties: vec!["Orthopedics".to_string(), "Cardiology".to_string()],
+            on_divert: false,
+            trauma_center: true,
+            stroke_ready: false,
+            cath_lab: true,
+            pediatric: false,
+            burn_unit: false,
         },
     ]
 }
@@ -1040,6 +7879,9 @@ fn create_demo_messages() -> Vec<ChatMessage> {
             message: "Patient showing signs of cardiac arrest. Administered epinephrine. Need cardiologist on standby.".to_string(),
             timestamp: Local::now() - chrono::Duration::minutes(1),
             urgent: true,
+            attachment: None,
+            mentions: vec![],
+            automated: false,
         },
         ChatMessage {
             id: Uuid::new_v4(),
@@ -1047,6 +7889,9 @@ fn create_demo_messages() -> Vec<ChatMessage> {
             message: "En route to hospital. ETA 3 minutes. Preparing cath lab.".to_string(),
             timestamp: Local::now() - chrono::Duration::minutes(2),
             urgent: false,
+            attachment: None,
+            mentions: vec![],
+            automated: false,
         },
         ChatMessage {
             id: Uuid::new_v4(),
@@ -1054,6 +7899,9 @@ fn create_demo_messages() -> Vec<ChatMessage> {
             message: "Trauma Bay 1 is ready. Blood bank notified for O-negative units.".to_string(),
             timestamp: Local::now() - chrono::Duration::minutes(3),
             urgent: false,
+            attachment: None,
+            mentions: vec![],
+            automated: false,
         },
         ChatMessage {
             id: Uuid::new_v4(),
@@ -1061,6 +7909,9 @@ fn create_demo_messages() -> Vec<ChatMessage> {
             message: "MVA patient stable but requesting Arabic-speaking physician for family communication.".to_string(),
             timestamp: Local::now() - chrono::Duration::minutes(4),
             urgent: true,
+            attachment: None,
+            mentions: vec![],
+            automated: false,
         },
     ]
 }
@@ -1078,6 +7929,741 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Dubai Healthcare Emergency Response System",
         options,
-        Box::new(|_cc| Box::new(EmergencyApp::default())),
+        Box::new(|cc| Box::new(EmergencyApp::new(cc))),
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn vitals(blood_pressure: (i32, i32), heart_rate: i32, oxygen_saturation: i32) -> VitalSigns {
+        VitalSigns {
+            blood_pressure,
+            heart_rate,
+            oxygen_saturation,
+            temperature: 37.0,
+        }
+    }
+    
+    fn hospital(name: &str, distance_minutes: u32, configure: impl FnOnce(&mut Hospital)) -> Hospital {
+        let mut hospital = Hospital {
+            name: name.to_string(),
+            available_beds: 5,
+            total_beds: 20,
+            distance_minutes,
+            specialties: vec![],
+            on_divert: false,
+            trauma_center: false,
+            stroke_ready: false,
+            cath_lab: false,
+            pediatric: false,
+            burn_unit: false,
+        };
+        configure(&mut hospital);
+        hospital
+    }
+    
+    fn patient(age: u8, chief_complaint: &str) -> Patient {
+        let mut patient = create_demo_patients().remove(0);
+        patient.age = age;
+        patient.chief_complaint = chief_complaint.to_string();
+        patient.assigned_hospital = None;
+        patient
+    }
+    
+    #[test]
+    fn routing_prefers_capable_hospital_over_nearer_one() {
+        let hospitals = vec![
+            hospital("Near", 5, |_| {}),
+            hospital("Cath", 15, |h| h.cath_lab = true),
+        ];
+        let chosen = recommend_hospital(&patient(60, "Chest Pain"), &hospitals, &[]).unwrap();
+        assert_eq!(chosen.name, "Cath");
+    }
+    
+    #[test]
+    fn routing_requires_every_needed_capability() {
+        let hospitals = vec![
+            hospital("Trauma", 5, |h| h.trauma_center = true),
+            hospital("Both", 20, |h| {
+                h.trauma_center = true;
+                h.pediatric = true;
+            }),
+        ];
+        let chosen = recommend_hospital(&patient(8, "Motor Vehicle Accident"), &hospitals, &[]).unwrap();
+        assert_eq!(chosen.name, "Both");
+    }
+    
+    #[test]
+    fn routing_skips_capable_hospital_without_beds_or_on_divert() {
+        let hospitals = vec![
+            hospital("Full", 5, |h| {
+                h.stroke_ready = true;
+                h.available_beds = 0;
+            }),
+            hospital("Divert", 6, |h| {
+                h.stroke_ready = true;
+                h.on_divert = true;
+            }),
+            hospital("Open", 12, |h| h.stroke_ready = true),
+        ];
+        let chosen = recommend_hospital(&patient(70, "Stroke symptoms"), &hospitals, &[]).unwrap();
+        assert_eq!(chosen.name, "Open");
+    }
+    
+    #[test]
+    fn routing_falls_back_to_nearest_when_no_hospital_is_capable() {
+        let hospitals = vec![hospital("Far", 20, |_| {}), hospital("Near", 5, |_| {})];
+        let chosen = recommend_hospital(&patient(30, "Severe burns"), &hospitals, &[]).unwrap();
+        assert_eq!(chosen.name, "Near");
+    }
+    
+    #[test]
+    fn routing_protocol_overrides_nearest() {
+        let hospitals = vec![
+            hospital("Near", 5, |h| h.stroke_ready = true),
+            hospital("Protocol", 25, |_| {}),
+        ];
+        let rules = vec![RoutingRule {
+            keyword: "stroke".to_string(),
+            route_to: RouteTo::Hospital("Protocol".to_string()),
+        }];
+        let stroke = patient(70, "Stroke symptoms");
+        
+        assert_eq!(recommend_hospital(&stroke, &hospitals, &rules).unwrap().name, "Protocol");
+        assert!(routing_note(&stroke, &hospitals, &rules).is_none());
+    }
+    
+    #[test]
+    fn routing_protocol_falls_back_with_note_when_full() {
+        let hospitals = vec![
+            hospital("Near", 5, |h| h.stroke_ready = true),
+            hospital("Protocol", 25, |h| h.available_beds = 0),
+        ];
+        let rules = vec![RoutingRule {
+            keyword: "stroke".to_string(),
+            route_to: RouteTo::Hospital("Protocol".to_string()),
+        }];
+        let stroke = patient(70, "Stroke symptoms");
+        
+        assert_eq!(recommend_hospital(&stroke, &hospitals, &rules).unwrap().name, "Near");
+        assert!(routing_note(&stroke, &hospitals, &rules).is_some_and(|n| n.contains("Protocol")));
+    }
+    
+    fn history(scores: &[(i32, i32)]) -> Vec<VitalsSample> {
+        scores
+            .iter()
+            .map(|&(heart_rate, oxygen_saturation)| VitalsSample {
+                vitals: vitals((120, 80), heart_rate, oxygen_saturation),
+                recorded_at: Local::now(),
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn trend_needs_enough_samples() {
+        assert_eq!(VitalsTrend::from_history(&history(&[(75, 98), (120, 90)])), VitalsTrend::InsufficientData);
+    }
+    
+    #[test]
+    fn trend_classifies_news2_slope() {
+        let worsening = history(&[(75, 98), (95, 95), (115, 93), (135, 90)]);
+        let recovering = history(&[(135, 90), (115, 93), (95, 95), (75, 98)]);
+        let steady = history(&[(75, 98), (76, 98), (74, 97), (75, 98)]);
+        
+        assert_eq!(VitalsTrend::from_history(&worsening), VitalsTrend::Deteriorating);
+        assert_eq!(VitalsTrend::from_history(&recovering), VitalsTrend::Improving);
+        assert_eq!(VitalsTrend::from_history(&steady), VitalsTrend::Stable);
+    }
+    
+    #[test]
+    fn shift_report_for_empty_range_reports_zeros() {
+        let end = Local::now();
+        let start = end - chrono::Duration::hours(2);
+        let report = shift_report_markdown(&[], &[], start, end);
+        
+        assert!(report.contains("| **Total** | 0 |"));
+        assert!(report.contains("Average wait: 0.0 min"));
+        assert!(report.contains("Max wait: 0 min"));
+        assert_eq!(report.matches("| 0 |").count(), TriageLevel::ALL.len() + 1 + 2);
+    }
+    
+    #[test]
+    fn shift_report_waits_skip_accepted_patients_without_a_time() {
+        let end = Local::now();
+        let start = end - chrono::Duration::hours(2);
+        let arrived = end - chrono::Duration::minutes(60);
+        
+        let mut waiting = patient(40, "Abdominal pain");
+        waiting.timestamp = arrived;
+        let mut accepted = waiting.clone();
+        accepted.accepted = true;
+        accepted.accepted_at = Some(arrived + chrono::Duration::minutes(20));
+        let mut untimed = waiting.clone();
+        untimed.accepted = true;
+        
+        let report = shift_report_markdown(&[&waiting, &accepted, &untimed], &[], start, end);
+        assert!(report.contains("Average wait: 40.0 min"));
+        assert!(report.contains("Max wait: 60 min"));
+    }
+    
+    #[test]
+    fn plausible_vitals_have_no_warnings() {
+        assert!(vitals_sanity_check(&vitals((120, 80), 75, 98)).is_empty());
+    }
+    
+    #[test]
+    fn systolic_below_diastolic_is_flagged() {
+        let warnings = vitals_sanity_check(&vitals((80, 120), 75, 98));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].to_string().contains("transposed"));
+    }
+    
+    #[test]
+    fn systolic_equal_to_diastolic_is_flagged() {
+        assert_eq!(vitals_sanity_check(&vitals((90, 90), 75, 98)).len(), 1);
+    }
+    
+    #[test]
+    fn zero_heart_rate_with_saturation_and_pressure_is_flagged() {
+        let warnings = vitals_sanity_check(&vitals((120, 80), 0, 99));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|w| w.to_string().contains("O2 sat 99%")));
+        assert!(warnings.iter().any(|w| w.to_string().contains("blood pressure")));
+    }
+    
+    #[test]
+    fn bounds_valid_readings_can_still_be_implausible() {
+        let reading = vitals((60, 110), 0, 97);
+        assert!(validate_vitals(&reading).is_empty());
+        assert_eq!(vitals_sanity_check(&reading).len(), 3);
+    }
+    
+    #[test]
+    fn resolved_condition_alerts_fade_out_but_manual_alerts_stay() {
+        let now = Local::now();
+        let divert = || vec![(AlertSource::HospitalUnavailable("Rashid".to_string()), AlertKind::Warning, "Rashid is on divert".to_string())];
+        let mut alerts = vec![Alert {
+            kind: AlertKind::Info,
+            source: AlertSource::Manual,
+            message: "Generator test at 14:00".to_string(),
+            raised_at: now,
+            resolved_at: None,
+        }];
+        
+        sync_alerts(&mut alerts, divert(), now, true);
+        sync_alerts(&mut alerts, divert(), now, true);
+        assert_eq!(alerts.len(), 2);
+        
+        sync_alerts(&mut alerts, vec![], now, true);
+        assert_eq!(alerts[1].resolved_at, Some(now));
+        
+        sync_alerts(&mut alerts, vec![], now + chrono::Duration::seconds(10), true);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].source, AlertSource::Manual);
+    }
+    
+    #[test]
+    fn resolved_alerts_linger_without_auto_dismiss() {
+        let now = Local::now();
+        let stale = vec![(AlertSource::StaleTelemetry("P1".to_string()), AlertKind::Warning, "stale".to_string())];
+        let mut alerts = vec![];
+        
+        sync_alerts(&mut alerts, stale.clone(), now, false);
+        sync_alerts(&mut alerts, vec![], now + chrono::Duration::minutes(5), false);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].resolved_at.is_some());
+        
+        // The condition coming back raises a fresh alert alongside the resolved one
+        sync_alerts(&mut alerts, stale, now + chrono::Duration::minutes(6), false);
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts[1].resolved_at.is_none());
+    }
+    
+    #[test]
+    fn resolved_alerts_are_capped_without_auto_dismiss() {
+        let now = Local::now();
+        let stale = vec![(AlertSource::StaleTelemetry("P1".to_string()), AlertKind::Warning, "stale".to_string())];
+        let mut alerts = vec![];
+        
+        for minute in 0..(MAX_RESOLVED_ALERTS as i64 + 5) {
+            sync_alerts(&mut alerts, stale.clone(), now + chrono::Duration::minutes(2 * minute), false);
+            sync_alerts(&mut alerts, vec![], now + chrono::Duration::minutes(2 * minute + 1), false);
+        }
+        
+        assert_eq!(alerts.len(), MAX_RESOLVED_ALERTS);
+        assert_eq!(alerts[0].raised_at, now + chrono::Duration::minutes(10));
+    }
+    
+    #[test]
+    fn escalation_mentions_follow_routes_without_duplicates() {
+        let routes = vec![
+            EscalationRoute { event: EscalationEvent::CodeCalled, mention: StaffRole::ChargeNurse },
+            EscalationRoute { event: EscalationEvent::CodeCalled, mention: StaffRole::Physician },
+            EscalationRoute { event: EscalationEvent::CodeCalled, mention: StaffRole::ChargeNurse },
+        ];
+        
+        assert_eq!(
+            escalation_mentions(&routes, EscalationEvent::CodeCalled),
+            vec![StaffRole::ChargeNurse, StaffRole::Physician]
+        );
+        assert!(escalation_mentions(&routes, EscalationEvent::NeedsAttention).is_empty());
+    }
+    
+    #[test]
+    fn reroute_is_suggested_only_when_assigned_destination_cannot_accept() {
+        let mut inbound = patient(45, "Abdominal pain");
+        inbound.status = PatientStatus::EnRoute;
+        inbound.assigned_hospital = Some("Assigned".to_string());
+        
+        let open = vec![hospital("Assigned", 10, |_| {}), hospital("Other", 20, |_| {})];
+        assert!(reroute_suggestion(&inbound, &open, &[]).is_none());
+        
+        let diverting = vec![hospital("Assigned", 10, |h| h.on_divert = true), hospital("Other", 20, |_| {})];
+        assert_eq!(reroute_suggestion(&inbound, &diverting, &[]).map(|h| h.name.as_str()), Some("Other"));
+        
+        let full = vec![hospital("Assigned", 10, |h| h.available_beds = 0), hospital("Other", 20, |_| {})];
+        assert_eq!(reroute_suggestion(&inbound, &full, &[]).map(|h| h.name.as_str()), Some("Other"));
+        
+        let nowhere = vec![hospital("Assigned", 10, |h| h.on_divert = true), hospital("Other", 20, |h| h.available_beds = 0)];
+        assert!(unavailable_destination(&inbound, &nowhere).is_some());
+        assert!(reroute_suggestion(&inbound, &nowhere, &[]).is_none());
+        
+        inbound.status = PatientStatus::InTreatment;
+        assert!(unavailable_destination(&inbound, &full).is_none());
+        assert!(reroute_suggestion(&inbound, &full, &[]).is_none());
+    }
+    
+    #[test]
+    fn board_set_eta_restarts_countdown_without_refreshing_telemetry() {
+        let now = Local::now();
+        let mut inbound = patient(45, "Abdominal pain");
+        inbound.last_updated = now - chrono::Duration::minutes(5);
+        inbound.eta_minutes = Some(12);
+        inbound.eta_set_at = Some(now);
+        
+        let countdown = inbound.eta_countdown(now, 120).unwrap();
+        assert_eq!(countdown.remaining_secs, 12 * 60);
+        assert!(countdown.stale);
+    }
+    
+    #[test]
+    fn every_card_action_preset_places_each_action_once() {
+        for role in StaffRole::ALL {
+            let slots = card_action_preset(role);
+            assert_eq!(slots.len(), CardAction::ALL.len(), "{:?}", role);
+            for action in CardAction::ALL {
+                assert_eq!(slots.iter().filter(|slot| slot.action == action).count(), 1, "{:?} {:?}", role, action);
+            }
+        }
+    }
+    
+    #[test]
+    fn integrity_checks_flag_inconsistent_records() {
+        let mut first = patient(40, "Chest pain");
+        first.status = PatientStatus::EnRoute;
+        first.eta_minutes = Some(8);
+        first.ambulance_id = None;
+        first.assigned_hospital = Some("Nowhere".to_string());
+        first.triage_level = TriageLevel::Low;
+        first.vitals = vitals((80, 120), 75, 84);
+        let duplicate = first.clone();
+        let hospitals = vec![hospital("Overfull", 10, |h| h.available_beds = 30)];
+        
+        let issues = run_integrity_checks(&[first], &[duplicate], &hospitals, &[]);
+        let messages: Vec<String> = issues.iter().map(|i| i.message.clone()).collect();
+        
+        assert!(messages.iter().any(|m| m.contains("more than one")));
+        assert!(messages.iter().any(|m| m.contains("no ambulance")));
+        assert!(messages.iter().any(|m| m.contains("unknown hospital")));
+        assert!(messages.iter().any(|m| m.contains("exceeds")));
+        assert!(messages.iter().any(|m| m.contains("Triaged LOW")));
+        assert!(messages.iter().any(|m| m.contains("transposed")));
+        assert_eq!(issues.iter().filter(|i| i.target == IssueTarget::Hospital("Overfull".to_string())).count(), 1);
+    }
+    
+    #[test]
+    fn acuity_mix_replays_triage_changes() {
+        let end = Local::now();
+        let start = end - chrono::Duration::hours(1);
+        
+        let mut escalated = patient(60, "Shortness of breath");
+        escalated.timestamp = start - chrono::Duration::minutes(10);
+        escalated.status = PatientStatus::InTreatment;
+        escalated.triage_level = TriageLevel::Critical;
+        escalated.triage_history = vec![TriageChange {
+            at: start + chrono::Duration::minutes(30),
+            from: TriageLevel::Medium,
+            to: TriageLevel::Critical,
+        }];
+        
+        assert_eq!(escalated.triage_at(start), TriageLevel::Medium);
+        assert_eq!(escalated.triage_at(end), TriageLevel::Critical);
+        
+        let samples = acuity_mix(&[&escalated], start, end);
+        assert_eq!(samples.len(), ACUITY_SAMPLES);
+        assert_eq!(samples[0], [0, 0, 1, 0]);
+        assert_eq!(samples[ACUITY_SAMPLES - 1], [1, 0, 0, 0]);
+    }
+    
+    #[test]
+    fn acuity_mix_is_flat_without_changes_in_range() {
+        let end = Local::now();
+        let start = end - chrono::Duration::hours(1);
+        let mut stable = patient(30, "Sprained ankle");
+        stable.timestamp = end - chrono::Duration::minutes(5);
+        stable.status = PatientStatus::InTriage;
+        stable.triage_level = TriageLevel::Low;
+        stable.triage_history.clear();
+        
+        let samples = acuity_mix(&[&stable], start, end);
+        assert!(samples.iter().all(|counts| *counts == [0, 0, 0, 1]));
+    }
+    
+    #[test]
+    fn parse_requested_triage_accepts_levels_blank_and_auto() {
+        assert_eq!(TriageLevel::parse_requested("critical"), Ok(Some(TriageLevel::Critical)));
+        assert_eq!(TriageLevel::parse_requested(" Low "), Ok(Some(TriageLevel::Low)));
+        assert_eq!(TriageLevel::parse_requested(""), Ok(None));
+        assert_eq!(TriageLevel::parse_requested("AUTO"), Ok(None));
+        assert!(TriageLevel::parse_requested("urgent").is_err_and(|e| e.contains("urgent")));
+    }
+    
+    #[test]
+    fn csv_intake_parses_with_and_without_vitals() {
+        let full = PatientIntake::from_csv_line("54, M, Chest pain, Sheikh Zayed Rd, high, 150, 95, 110, 93, 37.8").unwrap();
+        assert_eq!(full.age, 54);
+        assert_eq!(full.chief_complaint, "Chest pain");
+        assert_eq!(full.triage, Some(TriageLevel::High));
+        assert!(full.vitals.as_ref().is_some_and(|v| v.blood_pressure == (150, 95) && v.heart_rate == 110));
+        
+        let bare = PatientIntake::from_csv_line("30,F,Ankle injury,Marina,").unwrap();
+        assert_eq!(bare.triage, None);
+        assert!(bare.vitals.is_none());
+        
+        // Partial vitals count as not taken
+        let partial = PatientIntake::from_csv_line("30,F,Ankle injury,Marina,auto,120,80,,98,37").unwrap();
+        assert!(partial.vitals.is_none());
+    }
+    
+    #[test]
+    fn csv_intake_reports_bad_fields() {
+        let error = |line: &str| PatientIntake::from_csv_line(line).unwrap_err();
+        
+        assert!(error("54,M,Chest pain").contains("at least 5 columns"));
+        assert!(error("old,M,Chest pain,Marina,high").contains("invalid age"));
+        assert!(error("54,M,Chest pain,Marina,purple").contains("unknown triage level"));
+        assert!(error("54,M,Chest pain,Marina,high,150,x,110,93,37").contains("invalid vitals value"));
+        assert!(error("54,M,Chest pain,Marina,high,150,95,110,93,warm").contains("invalid temperature"));
+        assert!(error("54,M,Chest pain,Marina,high,80,120,110,93,37").contains("transposed"));
+    }
+    
+    #[test]
+    fn resolve_triage_prefers_explicit_then_vitals_then_review() {
+        let mut intake = PatientIntake::from_csv_line("70,M,Collapse,Deira,,85,50,130,86,36.5").unwrap();
+        assert_eq!(intake.resolve_triage(true), (TriageLevel::Critical, false));
+        assert_eq!(intake.resolve_triage(false), (TriageLevel::Medium, true));
+        
+        intake.triage = Some(TriageLevel::Low);
+        assert_eq!(intake.resolve_triage(true), (TriageLevel::Low, false));
+        
+        intake.triage = None;
+        intake.vitals = None;
+        assert_eq!(intake.resolve_triage(true), (TriageLevel::Medium, true));
+    }
+    
+    #[test]
+    fn v2_state_gains_hospital_capabilities_on_load() {
+        let mut state = EmergencyApp::default().saved_state();
+        state.version = 2;
+        state.hospitals.push(hospital("Al Zahra", 18, |h| {
+            h.specialties = vec!["Burns".to_string(), "Pediatrics".to_string()];
+        }));
+        for hospital in &mut state.hospitals {
+            hospital.trauma_center = false;
+            hospital.stroke_ready = false;
+            hospital.cath_lab = false;
+            hospital.pediatric = false;
+            hospital.burn_unit = false;
+        }
+        let blob = ron::to_string(&state).unwrap();
+        
+        let loaded = SavedState::load(&blob).unwrap();
+        assert_eq!(loaded.version, STATE_VERSION);
+        let find = |name: &str| loaded.hospitals.iter().find(|h| h.name == name).unwrap();
+        assert!(find("Rashid Hospital").has(Capability::Stroke));
+        assert!(find("Rashid Hospital").has(Capability::Burns));
+        assert!(find("Dubai Hospital").has(Capability::CathLab));
+        assert!(find("Al Zahra").has(Capability::Burns));
+        assert!(find("Al Zahra").has(Capability::Pediatric));
+        assert!(!find("Al Zahra").has(Capability::Trauma));
+    }
+    
+    #[test]
+    fn capabilities_match_whole_words_only() {
+        assert!(patient(40, "Heartburn").needed_capabilities().is_empty());
+        assert!(patient(62, "Unstable angina").needed_capabilities().is_empty());
+        assert!(patient(30, "Heatstroke").needed_capabilities().is_empty());
+        
+        assert_eq!(patient(40, "Burns to forearm").needed_capabilities(), vec![Capability::Burns]);
+        assert_eq!(patient(25, "Stab wound, abdomen").needed_capabilities(), vec![Capability::Trauma]);
+        assert_eq!(patient(71, "Facial droop / slurred speech").needed_capabilities(), vec![Capability::Stroke]);
+        assert_eq!(patient(55, "Chest pain").needed_capabilities(), vec![Capability::CathLab]);
+    }
+    
+    #[test]
+    fn routing_rules_match_whole_words() {
+        let rules = Settings::default().routing_rules;
+        let burn = rules.iter().find(|r| r.keyword == "burn").unwrap();
+        assert!(!burn.matches(&patient(40, "Heartburn")));
+        assert!(burn.matches(&patient(40, "Burn to left hand")));
+        assert!(burn.matches(&patient(40, "Burns, 20% TBSA")));
+        
+        let stroke = rules.iter().find(|r| r.keyword == "stroke").unwrap();
+        assert!(!stroke.matches(&patient(30, "Heatstroke")));
+        assert!(stroke.matches(&patient(70, "Suspected stroke")));
+    }
+    
+    #[test]
+    fn error_cap_keeps_banners_that_never_expire() {
+        let mut app = EmergencyApp::default();
+        app.errors.clear();
+        app.push_error(AppError::StateLoad("corrupt".to_string()));
+        for n in 0..MAX_ERROR_BANNERS + 2 {
+            app.push_error(AppError::FileWrite { path: format!("report-{}.md", n), message: "denied".to_string() });
+        }
+        
+        assert_eq!(app.errors.len(), MAX_ERROR_BANNERS);
+        assert!(matches!(app.errors[0].1, AppError::StateLoad(_)));
+    }
+    
+    #[test]
+    fn vitals_retention_keeps_newest_samples_or_recent_window() {
+        let now = Local::now();
+        let history: Vec<VitalsSample> = (0..10)
+            .map(|i| VitalsSample {
+                vitals: vitals((120, 80), 75, 98),
+                recorded_at: now - chrono::Duration::minutes(9 - i),
+            })
+            .collect();
+        
+        let mut by_count = history.clone();
+        VitalsRetention::LastSamples(4).apply(&mut by_count, now);
+        assert_eq!(by_count.len(), 4);
+        assert_eq!(by_count[0].recorded_at, history[6].recorded_at);
+        
+        let mut by_time = history.clone();
+        VitalsRetention::LastMinutes(3).apply(&mut by_time, now);
+        assert_eq!(by_time.len(), 4);
+        assert!(by_time.iter().all(|sample| now - sample.recorded_at <= chrono::Duration::minutes(3)));
+        
+        let mut short = history[..2].to_vec();
+        VitalsRetention::LastSamples(4).apply(&mut short, now);
+        assert_eq!(short.len(), 2);
+    }
+    
+    #[test]
+    fn blood_pressure_boundaries_match_legend() {
+        let bp = |systolic, diastolic| vitals((systolic, diastolic), 75, 98).bp_status();
+        assert_eq!(bp(140, 80), TriageLevel::Low);
+        assert_eq!(bp(141, 80), TriageLevel::High);
+        assert_eq!(bp(180, 80), TriageLevel::High);
+        assert_eq!(bp(181, 80), TriageLevel::Critical);
+        assert_eq!(bp(120, 90), TriageLevel::Low);
+        assert_eq!(bp(120, 91), TriageLevel::High);
+        assert_eq!(bp(120, 120), TriageLevel::High);
+        assert_eq!(bp(120, 121), TriageLevel::Critical);
+        
+        assert_eq!(SYSTOLIC_THRESHOLDS.normal_text(), "≤ 140");
+        assert_eq!(SYSTOLIC_THRESHOLDS.high_text(), "141–180");
+        assert_eq!(SYSTOLIC_THRESHOLDS.critical_text(), "≥ 181");
+        assert_eq!(DIASTOLIC_THRESHOLDS.normal_text(), "≤ 90");
+        assert_eq!(DIASTOLIC_THRESHOLDS.high_text(), "91–120");
+        assert_eq!(DIASTOLIC_THRESHOLDS.critical_text(), "≥ 121");
+    }
+    
+    #[test]
+    fn heart_rate_boundaries_match_legend() {
+        let hr = |rate| vitals((120, 80), rate, 98).hr_status();
+        assert_eq!(hr(49), TriageLevel::Critical);
+        assert_eq!(hr(50), TriageLevel::High);
+        assert_eq!(hr(59), TriageLevel::High);
+        assert_eq!(hr(60), TriageLevel::Low);
+        assert_eq!(hr(100), TriageLevel::Low);
+        assert_eq!(hr(101), TriageLevel::High);
+        assert_eq!(hr(120), TriageLevel::High);
+        assert_eq!(hr(121), TriageLevel::Critical);
+        
+        assert_eq!(HEART_RATE_THRESHOLDS.normal_text(), "60–100");
+        assert_eq!(HEART_RATE_THRESHOLDS.high_text(), "50–59 or 101–120");
+        assert_eq!(HEART_RATE_THRESHOLDS.critical_text(), "< 50 or ≥ 121");
+    }
+    
+    #[test]
+    fn oxygen_boundaries_match_legend() {
+        let o2 = |saturation| vitals((120, 80), 75, saturation).o2_status();
+        assert_eq!(o2(89), TriageLevel::Critical);
+        assert_eq!(o2(90), TriageLevel::High);
+        assert_eq!(o2(94), TriageLevel::High);
+        assert_eq!(o2(95), TriageLevel::Low);
+        
+        assert_eq!(OXYGEN_THRESHOLDS.normal_text(), "≥ 95");
+        assert_eq!(OXYGEN_THRESHOLDS.high_text(), "90–94");
+        assert_eq!(OXYGEN_THRESHOLDS.critical_text(), "< 90");
+    }
+    
+    #[test]
+    fn temperature_boundaries_match_legend() {
+        let temp = |temperature| VitalSigns { temperature, ..vitals((120, 80), 75, 98) }.temp_status();
+        assert_eq!(temp(34.9), TriageLevel::Critical);
+        assert_eq!(temp(35.0), TriageLevel::High);
+        assert_eq!(temp(35.9), TriageLevel::High);
+        assert_eq!(temp(36.0), TriageLevel::Low);
+        assert_eq!(temp(37.9), TriageLevel::Low);
+        assert_eq!(temp(38.0), TriageLevel::High);
+        assert_eq!(temp(39.4), TriageLevel::High);
+        assert_eq!(temp(39.5), TriageLevel::Critical);
+        
+        assert_eq!(TEMPERATURE_THRESHOLDS.normal_text(), "36.0–37.9");
+        assert_eq!(TEMPERATURE_THRESHOLDS.high_text(), "35.0–35.9 or 38.0–39.4");
+        assert_eq!(TEMPERATURE_THRESHOLDS.critical_text(), "< 35.0 or ≥ 39.5");
+    }
+    
+    #[test]
+    fn attention_flag_flicker_posts_once() {
+        let now = Local::now();
+        let p1 = vec!["P1".to_string()];
+        let mut last_flagged = HashMap::new();
+        
+        assert_eq!(debounce_attention(&mut last_flagged, &p1, now), p1);
+        assert!(debounce_attention(&mut last_flagged, &[], now + chrono::Duration::seconds(5)).is_empty());
+        assert!(debounce_attention(&mut last_flagged, &p1, now + chrono::Duration::seconds(10)).is_empty());
+        
+        let later = now + chrono::Duration::seconds(10 + ATTENTION_REPOST_AFTER_SECS);
+        assert!(debounce_attention(&mut last_flagged, &[], later).is_empty());
+        assert_eq!(debounce_attention(&mut last_flagged, &p1, later), p1);
+    }
+    
+    #[test]
+    fn saved_state_round_trips() {
+        let app = EmergencyApp::default();
+        let blob = ron::to_string(&app.saved_state()).unwrap();
+        let loaded = SavedState::load(&blob).unwrap();
+        
+        assert_eq!(loaded.version, STATE_VERSION);
+        let ids = |patients: &[Patient]| patients.iter().map(|p| p.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&loaded.patients), ids(&app.patients));
+        let statuses = |patients: &[Patient]| patients.iter().map(|p| p.status).collect::<Vec<_>>();
+        assert_eq!(statuses(&loaded.patients), statuses(&app.patients));
+        assert_eq!(loaded.hospitals.len(), app.hospitals.len());
+        assert_eq!(loaded.ambulance_available, app.ambulance_available);
+        assert_eq!(ron::to_string(&loaded).unwrap(), blob);
+    }
+    
+    #[test]
+    fn v1_state_migrates_to_current() {
+        let mut state = EmergencyApp::default().saved_state();
+        state.version = 1;
+        for patient in &mut state.patients {
+            patient.status = PatientStatus::default();
+        }
+        let inbound: Vec<bool> = state.patients.iter().map(|p| p.eta_minutes.is_some()).collect();
+        let blob = ron::to_string(&state).unwrap();
+        
+        let loaded = SavedState::load(&blob).unwrap();
+        assert_eq!(loaded.version, STATE_VERSION);
+        for (patient, inbound) in loaded.patients.iter().zip(inbound) {
+            let expected = if inbound { PatientStatus::EnRoute } else { PatientStatus::InTriage };
+            assert_eq!(patient.status, expected, "{}", patient.id);
+        }
+    }
+    
+    #[test]
+    fn newer_or_unreadable_state_is_rejected() {
+        let mut state = EmergencyApp::default().saved_state();
+        state.version = STATE_VERSION + 1;
+        assert!(SavedState::load(&ron::to_string(&state).unwrap()).is_err());
+        assert!(SavedState::load("not a saved state").is_err());
+    }
+    
+    #[test]
+    fn status_line_follows_status_and_location() {
+        let mut p = patient(50, "Abdominal pain");
+        p.status = PatientStatus::InTriage;
+        p.location = "Dubai Hospital - Triage Room 3".to_string();
+        p.in_hospital_location = Some(InHospitalLocation {
+            building: "Main".to_string(),
+            floor: "2".to_string(),
+            bay: "Bay 4".to_string(),
+        });
+        assert_eq!(p.status_line(), "In Triage - Dubai Hospital - Triage Room 3");
+        
+        p.status = PatientStatus::Admitted;
+        assert_eq!(p.status_line(), "Admitted - Main • Floor 2 • Bay 4");
+    }
+    
+    #[test]
+    fn dispatched_ambulance_returns_to_pool_once() {
+        let mut app = EmergencyApp::default();
+        let id = app.admit_intake(PatientIntake::from_csv_line("40,M,Abdominal pain,Al Barsha,").unwrap());
+        let (available, en_route) = (app.ambulance_available, app.ambulance_en_route);
+        
+        app.dispatch_ambulance(&id);
+        assert_eq!((app.ambulance_available, app.ambulance_en_route), (available - 1, en_route + 1));
+        app.dispatch_ambulance(&id);
+        assert_eq!(app.ambulance_available, available - 1);
+        
+        app.release_ambulance(&id);
+        app.release_ambulance(&id);
+        assert_eq!((app.ambulance_available, app.ambulance_en_route), (available, en_route));
+    }
+    
+    #[test]
+    fn ambulance_ids_skip_units_of_archived_patients() {
+        let mut archived = patient(40, "Abdominal pain");
+        archived.ambulance_id = Some("AMB-DXB-007".to_string());
+        let mut active = patient(50, "Chest pain");
+        active.ambulance_id = Some("AMB-DXB-003".to_string());
+        
+        assert_eq!(next_ambulance_id([&active, &archived].into_iter()), "AMB-DXB-008");
+    }
+    
+    #[test]
+    fn arrival_buckets_count_each_patient_in_range() {
+        let end = Local::now();
+        let start = end - chrono::Duration::minutes(ARRIVAL_BUCKET_MINUTES * 3);
+        let mut early = patient(40, "Abdominal pain");
+        early.timestamp = start + chrono::Duration::minutes(1);
+        let mut late = early.clone();
+        late.timestamp = end;
+        let mut outside = early.clone();
+        outside.timestamp = start - chrono::Duration::minutes(1);
+        
+        assert_eq!(arrival_buckets(&[&early, &late, &outside], start, end), vec![1, 0, 1]);
+    }
+    
+    #[test]
+    fn sidebar_cache_is_reused_until_data_changes() {
+        let mut app = EmergencyApp::default();
+        app.selected_patient = Some(app.patients[0].id.clone());
+        let ctx = Context::default();
+        let frame = |app: &mut EmergencyApp| {
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                SidePanel::left("sidebar").show(ctx, |ui| app.render_sidebar(ui));
+            });
+        };
+        
+        for _ in 0..5 {
+            frame(&mut app);
+        }
+        assert_eq!(app.sidebar_cache.rebuilds, 1);
+        assert!(app.sidebar_cache.focus_destination.is_some());
+        
+        app.hospitals[0].on_divert = true;
+        app.sidebar_dirty = true;
+        frame(&mut app);
+        frame(&mut app);
+        assert_eq!(app.sidebar_cache.rebuilds, 2);
+    }
+}